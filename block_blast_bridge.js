@@ -0,0 +1,145 @@
+// 方块消除游戏与宿主页面的桥接插件（miniquad插件）
+// 必须在 mq_js_bundle.js 之后、load(...) 之前引入
+//
+// 宿主页面可以:
+//   BlockBlast.on(function (e) { console.log(e.type, e.value); });
+//   BlockBlast.pause(); BlockBlast.resume(); BlockBlast.restart(); BlockBlast.quitToMenu();
+// 或监听 window 上的 "blockblast:game_over" 等事件。
+// 游戏嵌入在iframe中时，事件也会通过postMessage发送给父页面:
+//   {source: "block_blast", event: "game_over", value: 1200}
+// 父页面可以发送 {target: "block_blast", command: "pause"} 控制游戏。
+// 只与允许的父页面源通信：在引入插件的script标签上用 data-parent-origin 指定，例如
+//   <script src="block_blast_bridge.js" data-parent-origin="https://example.com"></script>
+// 未指定时只允许与游戏同源的父页面。
+// 插件同时为游戏提供基于 localStorage 的存档（最高分和设置）和实时的设备像素比。
+(function () {
+    "use strict";
+
+    // 与 src/host.rs 中 HostEvent::code 的编号一致
+    var EVENT_NAMES = ["game_started", "score_changed", "game_over", "high_score", "paused", "resumed"];
+    // 与 src/host.rs 中 HostCommand::from_code 的编号一致
+    var COMMANDS = { pause: 0, resume: 1, restart: 2, quitToMenu: 3 };
+
+    var STORAGE_KEY = "block_blast_save";
+
+    // 允许通过postMessage通信的父页面源，null表示与游戏同源
+    var currentScript = document.currentScript;
+    var PARENT_ORIGIN = (currentScript && currentScript.getAttribute("data-parent-origin")) || null;
+
+    function isAllowedOrigin(origin) {
+        return origin === (PARENT_ORIGIN || window.location.origin);
+    }
+
+    var listeners = [];
+
+    function dispatch(kind, value) {
+        var name = EVENT_NAMES[kind];
+        if (name === undefined) {
+            return;
+        }
+        var detail = { type: name, value: value };
+
+        listeners.forEach(function (listener) {
+            try {
+                listener(detail);
+            } catch (err) {
+                console.error("BlockBlast监听器出错:", err);
+            }
+        });
+        window.dispatchEvent(new CustomEvent("blockblast:" + name, { detail: detail }));
+
+        if (window.parent && window.parent !== window) {
+            // "/" 表示只发送给同源的父页面
+            try {
+                window.parent.postMessage({ source: "block_blast", event: name, value: value }, PARENT_ORIGIN || "/");
+            } catch (err) {
+                console.warn("无法向父页面发送事件:", err);
+            }
+        }
+    }
+
+    function send(command, quiet) {
+        if (typeof wasm_exports === "undefined" || !wasm_exports || !wasm_exports.bb_host_command) {
+            if (!quiet) {
+                console.warn("游戏尚未加载完成，忽略命令:", command);
+            }
+            return false;
+        }
+        return wasm_exports.bb_host_command(COMMANDS[command]) === 1;
+    }
+
+    window.BlockBlast = {
+        on: function (listener) {
+            listeners.push(listener);
+        },
+        off: function (listener) {
+            listeners = listeners.filter(function (l) { return l !== listener; });
+        },
+        pause: function () { return send("pause"); },
+        resume: function () { return send("resume"); },
+        restart: function () { return send("restart"); },
+        quitToMenu: function () { return send("quitToMenu"); }
+    };
+
+    // 父页面通过postMessage发送的命令，只接受来自允许的源的父窗口的消息
+    window.addEventListener("message", function (e) {
+        if (e.source !== window.parent || e.source === window || !isAllowedOrigin(e.origin)) {
+            return;
+        }
+        var data = e.data;
+        if (data && data.target === "block_blast" && COMMANDS.hasOwnProperty(data.command)) {
+            send(data.command);
+        }
+    });
+
+    // 页面被隐藏或失去焦点时自动暂停，玩家回来后在暂停菜单中选择继续
+    document.addEventListener("visibilitychange", function () {
+        if (document.hidden) {
+            send("pause", true);
+        }
+    });
+    window.addEventListener("blur", function () {
+        send("pause", true);
+    });
+
+    miniquad_add_plugin({
+        register_plugin: function (importObject) {
+            importObject.env.bb_host_event = function (kind, value) {
+                dispatch(kind, value);
+            };
+            // 实时的设备像素比，供 src/display.rs 在浏览器缩放或换显示器后调整界面大小
+            importObject.env.bb_device_pixel_ratio = function () {
+                return window.devicePixelRatio || 1.0;
+            };
+            // 存档：与 src/save.rs 中的声明一致，字符串以UTF-8在WASM内存中传递
+            importObject.env.bb_storage_load = function (ptr, maxLen) {
+                var text;
+                try {
+                    text = window.localStorage.getItem(STORAGE_KEY);
+                } catch (err) {
+                    return -1;
+                }
+                if (text === null) {
+                    return -1;
+                }
+                var bytes = new TextEncoder().encode(text);
+                if (bytes.length > maxLen) {
+                    return -1;
+                }
+                new Uint8Array(wasm_memory.buffer, ptr, bytes.length).set(bytes);
+                return bytes.length;
+            };
+            importObject.env.bb_storage_save = function (ptr, len) {
+                var text = new TextDecoder().decode(new Uint8Array(wasm_memory.buffer, ptr, len));
+                try {
+                    window.localStorage.setItem(STORAGE_KEY, text);
+                } catch (err) {
+                    console.warn("无法保存游戏数据:", err);
+                }
+            };
+        },
+        on_init: function () {},
+        version: 1,
+        name: "block_blast_bridge"
+    });
+})();
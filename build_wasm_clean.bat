@@ -42,6 +42,15 @@ if exist canvas_fixer.js (
   echo   - 警告：未找到canvas_fixer.js文件！游戏可能无法正常显示尺寸。
 )
 
+REM 2.2.1 复制宿主页面桥接插件
+echo 2.2.1 复制block_blast_bridge.js文件到web目录...
+if exist block_blast_bridge.js (
+  copy /y block_blast_bridge.js web\block_blast_bridge.js > nul
+  echo   - block_blast_bridge.js文件复制成功
+) else (
+  echo   - 警告：未找到block_blast_bridge.js文件！宿主页面将无法接收游戏事件。
+)
+
 REM 2.3 检查并复制resource文件夹
 echo 2.3 检查resources文件夹...
 if exist resources (
@@ -0,0 +1,58 @@
+/* 方块消除游戏引擎的C ABI接口，对应 src/ffi.rs
+ * 导出这些函数的产物：
+ *   原生宿主：库 libblock_blast（cdylib，cargo build --lib）；
+ *   网页宿主：build_wasm_clean.bat 部署的 block_blast_bin.wasm（src/main.rs 同样声明了 ffi 模块），
+ *            通过 wasm 实例的同名导出函数调用。
+ * 所有函数都接受空指针，此时返回默认值。
+ */
+#ifndef BLOCK_BLAST_H
+#define BLOCK_BLAST_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* 常量，与 src/ffi.rs 中的同名常量一致 */
+#define BB_GRID_SIZE 8
+#define BB_PALETTE_SIZE 7
+#define BB_MAX_TRAY 5
+#define BB_CELL_EMPTY (-1)
+#define BB_CELL_INVALID (-2)
+#define BB_PLACE_INVALID (-1)
+
+typedef struct BBGame BBGame;
+
+/* 创建与释放 */
+BBGame *bb_game_new(uint32_t seed);
+/* blocks_per_generation 会被限制在1到BB_MAX_TRAY之间 */
+BBGame *bb_game_new_with_config(uint32_t seed, int32_t simple_chance,
+                                int32_t standard_chance, int32_t blocks_per_generation);
+void bb_game_free(BBGame *game);
+void bb_game_reset(BBGame *game, uint32_t seed);
+
+/* 网格：BB_CELL_EMPTY为空，0到BB_PALETTE_SIZE-1为颜色索引，坐标越界返回BB_CELL_INVALID */
+int32_t bb_game_cell(const BBGame *game, int32_t x, int32_t y);
+
+/* 候选方块 */
+int32_t bb_game_tray_len(const BBGame *game);
+int32_t bb_game_tray_cell_count(const BBGame *game, int32_t slot);
+int32_t bb_game_tray_cell_x(const BBGame *game, int32_t slot, int32_t i);
+int32_t bb_game_tray_cell_y(const BBGame *game, int32_t slot, int32_t i);
+int32_t bb_game_tray_color(const BBGame *game, int32_t slot);
+
+/* 放置：bb_game_place 返回消除的行列数，不可放置时返回BB_PLACE_INVALID */
+int32_t bb_game_can_place(const BBGame *game, int32_t slot, int32_t x, int32_t y);
+int32_t bb_game_place(BBGame *game, int32_t slot, int32_t x, int32_t y);
+
+/* 状态 */
+uint32_t bb_game_score(const BBGame *game);
+uint32_t bb_game_combo(const BBGame *game);
+int32_t bb_game_is_over(const BBGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BLOCK_BLAST_H */
@@ -0,0 +1,179 @@
+// 音效系统：启动时加载音效，按游戏事件播放
+// 默认使用 resources/disappear.wav，音频后端只提供音量参数，没有变调接口，
+// 因此通过改写WAV头中的采样率得到不同音高的版本
+// 启用 synth_sounds 特性时改为程序合成全部音效（见 synth.rs），不打包音频文件
+// 没有音频设备或音效无法加载时静默跳过，不影响游戏
+use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound};
+
+/// 每种游戏事件对应的音效
+/// 消除按行数（1、2、3、4行及以上）、连击按等级（2、3、4、5连击及以上）分档
+#[derive(Default)]
+pub struct SoundSet<T> {
+    pub place: T,
+    pub clear: [T; 4],
+    pub combo: [T; 4],
+    pub game_over: T,
+}
+
+impl<T> SoundSet<T> {
+    fn map<U>(self, mut f: impl FnMut(T) -> U) -> SoundSet<U> {
+        SoundSet {
+            place: f(self.place),
+            clear: self.clear.map(&mut f),
+            combo: self.combo.map(&mut f),
+            game_over: f(self.game_over),
+        }
+    }
+
+    // 按 map 的调用顺序展开
+    fn into_vec(self) -> Vec<T> {
+        let mut items = vec![self.place];
+        items.extend(self.clear);
+        items.extend(self.combo);
+        items.push(self.game_over);
+        items
+    }
+}
+
+/// 已加载的音效，加载失败的音效为None，播放时跳过
+#[derive(Default)]
+pub struct Sounds {
+    set: SoundSet<Option<Sound>>,
+    pub volume: f32,  // 音效总音量，0.0-1.0
+}
+
+impl Sounds {
+    /// 生成并加载所有音效
+    pub async fn load() -> Self {
+        let data = sound_data().into_vec();
+        let mut loaded = Vec::with_capacity(data.len());
+        for wav in &data {
+            loaded.push(match wav {
+                Some(wav) => load_wav(wav).await,
+                None => None,
+            });
+        }
+        println!("已加载音效: {}/{}", loaded.iter().filter(|sound| sound.is_some()).count(), loaded.len());
+
+        let mut loaded = loaded.into_iter();
+        let set = SoundSet::<()>::default().map(|_| loaded.next().flatten());
+        Sounds { set, volume: 1.0 }
+    }
+
+    /// 放置方块
+    pub fn play_place(&self) {
+        self.play(&self.set.place, 0.4);
+    }
+
+    /// 消除行列：消除越多音调越高、音量越大；连击时叠加一层连击音效
+    pub fn play_clear(&self, lines: u32, combo: u32) {
+        if lines == 0 {
+            return;
+        }
+        let idx = (lines as usize - 1).min(3);
+        self.play(&self.set.clear[idx], 0.6 + 0.1 * idx as f32);
+
+        if combo >= 2 {
+            let level = (combo as usize - 2).min(3);
+            self.play(&self.set.combo[level], 0.4 + 0.1 * level as f32);
+        }
+    }
+
+    /// 游戏结束
+    pub fn play_game_over(&self) {
+        self.play(&self.set.game_over, 0.8);
+    }
+
+    fn play(&self, sound: &Option<Sound>, volume: f32) {
+        if let Some(sound) = sound {
+            let volume = (volume * self.volume).clamp(0.0, 1.0);
+            if volume > 0.0 {
+                play_sound(sound, PlaySoundParams { looped: false, volume });
+            }
+        }
+    }
+}
+
+async fn load_wav(wav: &[u8]) -> Option<Sound> {
+    match load_sound_from_bytes(wav).await {
+        Ok(sound) => Some(sound),
+        Err(err) => {
+            println!("无法加载音效: {:?}", err);
+            None
+        }
+    }
+}
+
+// 程序合成全部音效
+#[cfg(feature = "synth_sounds")]
+fn sound_data() -> SoundSet<Option<Vec<u8>>> {
+    use crate::synth::{arpeggio, blip, chime, semitone, to_wav, Waveform};
+
+    // C5、E5、G5、C6，消除越多音调越高
+    const C5: f32 = 523.25;
+    let clear_steps = [0, 4, 7, 12];
+    // C大调琶音，连击越高音符越多
+    let combo_notes = [0, 4, 7, 12, 16, 19, 24];
+    // 下行的小调琶音
+    let game_over_notes = [7, 3, 0, -5, -12];
+
+    SoundSet {
+        place: blip(330.0, 0.08),
+        clear: clear_steps.map(|step| chime(semitone(C5, step), 0.4)),
+        combo: [3, 4, 5, 7].map(|notes| arpeggio(semitone(C5, 12), &combo_notes[..notes], 0.05, Waveform::Triangle)),
+        game_over: arpeggio(semitone(C5, -12), &game_over_notes, 0.16, Waveform::Triangle),
+    }
+    .map(|samples| Some(to_wav(&samples)))
+}
+
+// 由打包的消除音效变调得到全部音效
+#[cfg(not(feature = "synth_sounds"))]
+fn sound_data() -> SoundSet<Option<Vec<u8>>> {
+    // 消除音效（由 resources/disappear.mp3 转换的WAV，原生平台的音频后端不支持mp3）
+    const DISAPPEAR_SOUND_DATA: &[u8] = include_bytes!("../resources/disappear.wav");
+
+    SoundSet {
+        place: 0.7,
+        clear: [1.0, 1.12, 1.26, 1.5],
+        combo: [2.0, 2.24, 2.52, 3.0],
+        game_over: 0.5,
+    }
+    .map(|pitch| with_pitch(DISAPPEAR_SOUND_DATA, pitch))
+}
+
+// 按倍率改写WAV的采样率（同时改写字节率），播放时音高和速度一起变化
+// 不是WAV格式返回None
+#[cfg(not(feature = "synth_sounds"))]
+fn with_pitch(wav: &[u8], pitch: f32) -> Option<Vec<u8>> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return None;
+    }
+
+    // 依次查找fmt块
+    let mut pos = 12;
+    while pos + 8 <= wav.len() {
+        let id = &wav[pos..pos + 4];
+        let size = u32::from_le_bytes(wav[pos + 4..pos + 8].try_into().ok()?) as usize;
+        let body = pos + 8;
+        if id == b"fmt " {
+            if size < 16 || body + 16 > wav.len() {
+                return None;
+            }
+            let read_u32 = |at: usize| u32::from_le_bytes([wav[at], wav[at + 1], wav[at + 2], wav[at + 3]]);
+            let sample_rate = read_u32(body + 4);
+            let byte_rate = read_u32(body + 8);
+            let block_align = u16::from_le_bytes([wav[body + 12], wav[body + 13]]) as u32;
+
+            let new_rate = ((sample_rate as f32 * pitch).round() as u32).max(1);
+            let new_byte_rate = if block_align > 0 { new_rate * block_align } else { byte_rate };
+
+            let mut out = wav.to_vec();
+            out[body + 4..body + 8].copy_from_slice(&new_rate.to_le_bytes());
+            out[body + 8..body + 12].copy_from_slice(&new_byte_rate.to_le_bytes());
+            return Some(out);
+        }
+        // 块大小为奇数时有一个填充字节
+        pos = body + size + (size & 1);
+    }
+    None
+}
@@ -0,0 +1,381 @@
+// 强化学习环境的标准输入输出协议，每行一个JSON对象
+// 外部训练代码无需FFI即可驱动真实的游戏规则
+//
+// 请求:
+//   {"cmd": "spec"}                                     -> 动作空间等常量
+//   {"cmd": "reset", "seed": 1}                          -> {"observation": ...}
+//   {"cmd": "reset", "seed": 1, "simple_chance": 30, "standard_chance": 60, "blocks_per_generation": 3}
+//   {"cmd": "step", "action": 42}                        -> {"observation": ..., "reward": ..., "done": ..., "info": ...}
+//   {"cmd": "close"}                                     -> 退出
+// 出错时返回 {"error": "..."}，环境状态不变
+use std::fmt::Write as _;
+use std::io::{BufRead, Write as _};
+
+use block_blast::engine::GameConfig;
+use block_blast::env::{Env, Observation, StepResult, ACTION_COUNT, MAX_TRAY, SHAPE_SIZE};
+
+// 请求中的取值，协议只使用扁平对象
+#[derive(Debug, PartialEq)]
+enum Value {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+}
+
+fn main() {
+    let mut env = Env::new(GameConfig::default());
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, close) = match parse_object(&line) {
+            Ok(request) => handle(&mut env, &request),
+            Err(message) => (error_json(&message), false),
+        };
+
+        if writeln!(stdout, "{}", response).and_then(|_| stdout.flush()).is_err() || close {
+            break;
+        }
+    }
+}
+
+// 处理一条请求，返回响应和是否关闭
+fn handle(env: &mut Env, request: &[(String, Value)]) -> (String, bool) {
+    let get = |key: &str| request.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+    let number = |key: &str| match get(key) {
+        Some(Value::Num(n)) if *n >= 0.0 && n.fract() == 0.0 => Ok(Some(*n as u64)),
+        Some(_) => Err(format!("{} 必须是非负整数", key)),
+        None => Ok(None),
+    };
+
+    let cmd = match get("cmd") {
+        Some(Value::Str(cmd)) => cmd.as_str(),
+        _ => return (error_json("缺少cmd字段"), false),
+    };
+
+    match cmd {
+        "spec" => (
+            format!(
+                "{{\"action_count\": {}, \"max_tray\": {}, \"shape_size\": {}, \"grid_size\": 8, \"action_encoding\": \"slot*64+y*8+x\"}}",
+                ACTION_COUNT, MAX_TRAY, SHAPE_SIZE
+            ),
+            false,
+        ),
+        "reset" => {
+            let result = (|| {
+                // 先在副本上解析和检查所有字段，全部有效后才替换配置
+                let seed = number("seed")?.unwrap_or(0);
+                let mut config = env.engine().config;
+                if let Some(n) = number("simple_chance")? {
+                    config.simple_block_chance = n.min(100) as i32;
+                }
+                if let Some(n) = number("standard_chance")? {
+                    config.standard_block_chance = n.min(100) as i32;
+                }
+                if let Some(n) = number("blocks_per_generation")? {
+                    if !(1..=MAX_TRAY as u64).contains(&n) {
+                        return Err(format!("blocks_per_generation 应在1-{}之间", MAX_TRAY));
+                    }
+                    config.blocks_per_generation = n as usize;
+                }
+                Ok((seed, config))
+            })();
+            match result {
+                Ok((seed, config)) => {
+                    *env.config_mut() = config;
+                    let observation = env.reset(seed);
+                    (format!("{{\"observation\": {}}}", observation_json(&observation)), false)
+                }
+                Err(message) => (error_json(&message), false),
+            }
+        }
+        "step" => match number("action") {
+            Ok(Some(action)) => (step_json(&env.step(action as usize)), false),
+            Ok(None) => (error_json("缺少action字段"), false),
+            Err(message) => (error_json(&message), false),
+        },
+        "close" => ("{\"closed\": true}".to_string(), true),
+        other => (error_json(&format!("未知命令: {}", other)), false),
+    }
+}
+
+fn step_json(result: &StepResult) -> String {
+    let info = &result.info;
+    format!(
+        "{{\"observation\": {}, \"reward\": {}, \"done\": {}, \"info\": {{\"lines_cleared\": {}, \"points\": {}, \"combo\": {}, \"score\": {}, \"moves\": {}, \"invalid_action\": {}}}}}",
+        observation_json(&result.observation),
+        result.reward,
+        result.done,
+        info.lines_cleared,
+        info.points,
+        info.combo,
+        info.score,
+        info.moves,
+        info.invalid_action
+    )
+}
+
+fn observation_json(observation: &Observation) -> String {
+    let matrix = |rows: &mut dyn Iterator<Item = &[u8]>| {
+        let rows: Vec<String> = rows
+            .map(|row| {
+                let cells: Vec<String> = row.iter().map(u8::to_string).collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+        format!("[{}]", rows.join(","))
+    };
+
+    let grid = matrix(&mut observation.grid.iter().map(|row| &row[..]));
+    let tray: Vec<String> = observation.tray.iter()
+        .map(|shape| matrix(&mut shape.iter().map(|row| &row[..])))
+        .collect();
+
+    let mut mask = String::with_capacity(ACTION_COUNT * 2);
+    for (i, legal) in observation.action_mask.iter().enumerate() {
+        if i > 0 {
+            mask.push(',');
+        }
+        mask.push(if *legal { '1' } else { '0' });
+    }
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "{{\"grid\": {}, \"tray\": [{}], \"tray_len\": {}, \"action_mask\": [{}], \"score\": {}, \"combo\": {}}}",
+        grid,
+        tray.join(","),
+        observation.tray_len,
+        mask,
+        observation.score,
+        observation.combo
+    );
+    out
+}
+
+fn error_json(message: &str) -> String {
+    let mut escaped = String::with_capacity(message.len());
+    for c in message.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // 其余控制字符在JSON字符串中必须转义
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    format!("{{\"error\": \"{}\"}}", escaped)
+}
+
+// 解析扁平JSON对象 {"key": value, ...}，值只支持字符串、数字、布尔和null
+fn parse_object(text: &str) -> Result<Vec<(String, Value)>, String> {
+    let mut chars = text.trim().chars().peekable();
+    let mut fields = Vec::new();
+
+    let skip_ws = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    };
+
+    if chars.next() != Some('{') {
+        return Err("请求必须是JSON对象".to_string());
+    }
+    skip_ws(&mut chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return Ok(fields);
+    }
+
+    loop {
+        skip_ws(&mut chars);
+        if chars.next() != Some('"') {
+            return Err("字段名必须是字符串".to_string());
+        }
+        let key = parse_string(&mut chars)?;
+        skip_ws(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("字段 {} 后缺少冒号", key));
+        }
+        skip_ws(&mut chars);
+
+        let value = match chars.peek() {
+            Some('"') => {
+                chars.next();
+                Value::Str(parse_string(&mut chars)?)
+            }
+            Some(c) if c.is_ascii_digit() || *c == '-' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                Value::Num(number.parse().map_err(|_| format!("无效的数字: {}", number))?)
+            }
+            Some(_) => {
+                let mut word = String::new();
+                while chars.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    word.push(chars.next().unwrap());
+                }
+                match word.as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => return Err(format!("字段 {} 的值不受支持", key)),
+                }
+            }
+            None => return Err("请求不完整".to_string()),
+        };
+        fields.push((key, value));
+
+        skip_ws(&mut chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => break,
+            _ => return Err("缺少逗号或右括号".to_string()),
+        }
+    }
+
+    skip_ws(&mut chars);
+    if chars.next().is_some() {
+        return Err("对象之后有多余内容".to_string());
+    }
+    Ok(fields)
+}
+
+// 读取字符串内容，开头的引号已被读取
+fn parse_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    let mut out = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(out),
+            Some('\\') => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                Some('b') => out.push('\u{8}'),
+                Some('f') => out.push('\u{c}'),
+                Some('u') => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&hex, 16).map_err(|_| "无效的转义".to_string())?;
+                    out.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                }
+                Some(c) => out.push(c),
+                None => return Err("字符串未结束".to_string()),
+            },
+            Some(c) => out.push(c),
+            None => return Err("字符串未结束".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(text: &str) -> Vec<(String, Value)> {
+        parse_object(text).unwrap()
+    }
+
+    #[test]
+    fn parse_handles_whitespace_and_values() {
+        let fields = request("  { \"cmd\" :\"step\" ,\n\t\"action\":42, \"x\": -1.5e1, \"on\": true, \"off\":false, \"n\" : null }  ");
+        assert_eq!(fields, vec![
+            ("cmd".to_string(), Value::Str("step".to_string())),
+            ("action".to_string(), Value::Num(42.0)),
+            ("x".to_string(), Value::Num(-15.0)),
+            ("on".to_string(), Value::Bool(true)),
+            ("off".to_string(), Value::Bool(false)),
+            ("n".to_string(), Value::Null),
+        ]);
+        assert!(request("{}").is_empty());
+        assert!(request(" { } ").is_empty());
+    }
+
+    #[test]
+    fn parse_handles_escapes() {
+        let fields = request(r#"{"s": "a\"b\\c\/d\ne\rf\tg\u00e9\u4e2d"}"#);
+        assert_eq!(fields[0].1, Value::Str("a\"b\\c/d\ne\rf\tg\u{e9}中".to_string()));
+        // 键名同样支持转义
+        assert_eq!(request(r#"{"c\u006dd": 1}"#)[0].0, "cmd");
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        for text in [
+            "",
+            "[]",
+            "{",
+            "{\"cmd\"}",
+            "{\"cmd\": }",
+            "{cmd: 1}",
+            "{\"cmd\": tru}",
+            "{\"cmd\": \"spec\",}",
+            "{\"cmd\": \"spec\" \"seed\": 1}",
+            "{\"cmd\": \"spec\"} x",
+            "{\"cmd\": \"spec",
+            "{\"cmd\": \"\\u12\"}",
+            "{\"n\": 1-2}",
+        ] {
+            assert!(parse_object(text).is_err(), "应拒绝: {}", text);
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_ignored() {
+        let mut env = Env::new(GameConfig::default());
+        let (response, close) = handle(&mut env, &request("{\"cmd\": \"spec\", \"extra\": 1}"));
+        assert!(response.contains("\"action_count\": 320"));
+        assert!(!close);
+    }
+
+    #[test]
+    fn invalid_reset_keeps_config() {
+        let mut env = Env::new(GameConfig::default());
+        let before = env.engine().config;
+        let (response, _) = handle(
+            &mut env,
+            &request("{\"cmd\": \"reset\", \"seed\": 1, \"simple_chance\": 5, \"blocks_per_generation\": 9}"),
+        );
+        assert!(response.starts_with("{\"error\""));
+        assert_eq!(env.engine().config, before);
+
+        let (response, _) = handle(
+            &mut env,
+            &request("{\"cmd\": \"reset\", \"seed\": 1, \"simple_chance\": 5, \"blocks_per_generation\": 4}"),
+        );
+        assert!(response.starts_with("{\"observation\""));
+        assert_eq!(env.engine().config.simple_block_chance, 5);
+        assert_eq!(env.engine().config.blocks_per_generation, 4);
+        assert_eq!(env.engine().current_blocks.len(), 4);
+    }
+
+    #[test]
+    fn invalid_step_reports_error_state() {
+        let mut env = Env::new(GameConfig::default());
+        handle(&mut env, &request("{\"cmd\": \"reset\", \"seed\": 2}"));
+        let (response, _) = handle(&mut env, &request(&format!("{{\"cmd\": \"step\", \"action\": {}}}", ACTION_COUNT)));
+        assert!(response.contains("\"invalid_action\": true"));
+        assert!(handle(&mut env, &request("{\"cmd\": \"step\", \"action\": -1}")).0.starts_with("{\"error\""));
+        assert!(handle(&mut env, &request("{\"cmd\": \"step\"}")).0.starts_with("{\"error\""));
+    }
+
+    #[test]
+    fn error_json_escapes_control_characters() {
+        assert_eq!(error_json("a\"b\\c"), r#"{"error": "a\"b\\c"}"#);
+        assert_eq!(error_json("1\n2\r3\t4\u{1}5"), r#"{"error": "1\n2\r3\t4\u00015"}"#);
+    }
+}
@@ -0,0 +1,151 @@
+// 无窗口截图工具：把棋盘记谱、回放的某一帧或机器人对局的局面渲染为PNG，不需要GPU
+// 示例: cargo run --bin screenshot -- --board "......../......../......../......../......../......../00000000/1111111. L:2,O:0 1200" --out board.png
+//       cargo run --bin screenshot -- --seed 7 --moves 30 --save-replay game.txt --out move30.png
+//       cargo run --bin screenshot -- --replay game.txt --frame 12 --theme dark --scale 2 --out move12.png
+use block_blast::bot::BotStrength;
+use block_blast::engine::GameConfig;
+use block_blast::replay::Replay;
+use block_blast::snapshot::Snapshot;
+use block_blast::theme;
+use macroquad::math::Vec2;
+
+const USAGE: &str = "用法: screenshot (--board 记谱 | --replay 文件 | --seed S) --out PATH [选项]
+  --board N         棋盘记谱，格式见 src/snapshot.rs
+  --replay PATH     回放文件，格式见 src/replay.rs
+  --frame N         回放的前N步之后的局面 (默认最后一帧)
+  --seed S          由机器人从种子S开始玩
+  --moves N         机器人最多玩的步数 (默认20)
+  --policy P        机器人策略: random | greedy | lookahead (默认greedy)
+  --config A,B,C    简单方块概率,标准方块概率,每次生成方块数 (默认30,60,3)
+  --save-replay P   把机器人的对局保存为回放文件
+  --size WxH        画面的逻辑大小 (默认400x600)
+  --scale K         像素比，输出图片为逻辑大小的K倍 (默认1)
+  --theme NAME      内置主题 (默认classic)
+  --colorblind      色盲模式
+  --print-notation  在标准输出打印局面的棋盘记谱
+  --out PATH        输出的PNG文件";
+
+enum Source {
+    Board(String),
+    Replay(String),
+    Bot(u64),
+}
+
+struct Options {
+    source: Option<Source>,
+    frame: Option<usize>,
+    moves: u32,
+    strength: BotStrength,
+    config: GameConfig,
+    save_replay: Option<String>,
+    size: Vec2,
+    scale: f32,
+    theme: String,
+    colorblind: bool,
+    print_notation: bool,
+    out: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        source: None,
+        frame: None,
+        moves: 20,
+        strength: BotStrength::Greedy,
+        config: GameConfig::default(),
+        save_replay: None,
+        size: Vec2::new(400.0, 600.0),
+        scale: 1.0,
+        theme: theme::DEFAULT_THEME.to_string(),
+        colorblind: false,
+        print_notation: false,
+        out: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} 缺少参数值", arg));
+        match arg.as_str() {
+            "--board" => options.source = Some(Source::Board(value()?)),
+            "--replay" => options.source = Some(Source::Replay(value()?)),
+            "--seed" => options.source = Some(Source::Bot(parse_number(&value()?)?)),
+            "--frame" => options.frame = Some(parse_number(&value()?)?),
+            "--moves" => options.moves = parse_number(&value()?)?,
+            "--policy" => {
+                let name = value()?;
+                options.strength = BotStrength::from_name(&name).ok_or(format!("未知策略: {}", name))?;
+            }
+            "--config" => options.config = GameConfig::parse(&value()?)?,
+            "--save-replay" => options.save_replay = Some(value()?),
+            "--size" => {
+                let text = value()?;
+                let (width, height) = text.split_once('x').ok_or(format!("画面大小应写作 宽x高: {}", text))?;
+                options.size = Vec2::new(parse_number(width)?, parse_number(height)?);
+            }
+            "--scale" => options.scale = parse_number(&value()?)?,
+            "--theme" => options.theme = value()?,
+            "--colorblind" => options.colorblind = true,
+            "--print-notation" => options.print_notation = true,
+            "--out" => options.out = Some(value()?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("未知参数: {}\n{}", other, USAGE)),
+        }
+    }
+
+    if options.size.x < 1.0 || options.size.y < 1.0 || !(0.25..=8.0).contains(&options.scale) {
+        return Err("画面大小至少为1x1，像素比应在0.25-8之间".to_string());
+    }
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("无效的数字: {}", text))
+}
+
+// 按选项得到要渲染的局面
+fn load_snapshot(options: &Options) -> Result<Snapshot, String> {
+    let replay = match options.source.as_ref().ok_or(USAGE)? {
+        Source::Board(notation) => return Snapshot::parse(notation),
+        Source::Replay(path) => {
+            let source = std::fs::read_to_string(path).map_err(|err| format!("无法读取回放 {}: {}", path, err))?;
+            Replay::parse(&source)?
+        }
+        Source::Bot(seed) => Replay::record(options.config, options.strength, *seed, options.moves),
+    };
+    if let Some(path) = &options.save_replay {
+        std::fs::write(path, replay.to_text()).map_err(|err| format!("无法写入回放 {}: {}", path, err))?;
+    }
+    let engine = replay.frame(options.frame.unwrap_or(replay.moves.len()))?;
+    Ok(Snapshot::from_engine(&engine))
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let snapshot = load_snapshot(options)?;
+    if !theme::set_theme(&options.theme) {
+        return Err(format!("未知主题: {}，可选: {}", options.theme, theme::names().join(", ")));
+    }
+    theme::set_colorblind(options.colorblind);
+    if options.print_notation {
+        println!("{}", snapshot.notation());
+    }
+
+    let out = options.out.as_ref().ok_or(USAGE)?;
+    let canvas = snapshot.render(options.size, options.scale, &theme::current());
+    std::fs::write(out, canvas.encode_png()).map_err(|err| format!("无法写入 {}: {}", out, err))?;
+    eprintln!("已写入 {} ({}x{})", out, canvas.width(), canvas.height());
+    Ok(())
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    if let Err(message) = run(&options) {
+        eprintln!("{}", message);
+        std::process::exit(1);
+    }
+}
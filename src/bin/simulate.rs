@@ -0,0 +1,253 @@
+// 无界面批量模拟工具，用于调整方块生成概率和数量
+// 示例: cargo run --release --bin simulate -- --games 200 --policy lookahead --config 30,60,3 --config 20,60,3 --format json
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+use block_blast::bot::BotStrength;
+use block_blast::engine::GameConfig;
+use block_blast::simulation::{play_game, GameRecord, Summary};
+
+const USAGE: &str = "用法: simulate [选项]
+  --games N         每组配置的局数 (默认100)
+  --seed S          起始种子，第i局使用S+i (默认1)
+  --policy P        机器人策略: random | greedy | lookahead (默认greedy)
+  --config A,B,C    简单方块概率,标准方块概率,每次生成方块数，可重复指定多组 (默认30,60,3)
+  --max-moves N     每局最多步数，超过后按move_limit结束 (默认10000)
+  --format F        输出格式: csv | json (默认csv)
+  --per-game        csv输出每局明细而不是汇总；json在汇总中附带每局明细
+  --out PATH        写入文件而不是标准输出";
+
+struct Options {
+    games: u32,
+    seed: u64,
+    strength: BotStrength,
+    configs: Vec<GameConfig>,
+    max_moves: u32,
+    json: bool,
+    per_game: bool,
+    out: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        games: 100,
+        seed: 1,
+        strength: BotStrength::Greedy,
+        configs: Vec::new(),
+        max_moves: 10000,
+        json: false,
+        per_game: false,
+        out: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} 缺少参数值", arg));
+        match arg.as_str() {
+            "--games" => options.games = parse_number(&value()?)?,
+            "--seed" => options.seed = parse_number(&value()?)?,
+            "--policy" => {
+                let name = value()?;
+                options.strength = BotStrength::from_name(&name).ok_or(format!("未知策略: {}", name))?;
+            }
+            "--config" => options.configs.push(GameConfig::parse(&value()?)?),
+            "--max-moves" => options.max_moves = parse_number(&value()?)?,
+            "--format" => {
+                options.json = match value()?.as_str() {
+                    "csv" => false,
+                    "json" => true,
+                    other => return Err(format!("未知输出格式: {}", other)),
+                }
+            }
+            "--per-game" => options.per_game = true,
+            "--out" => options.out = Some(value()?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            other => return Err(format!("未知参数: {}\n{}", other, USAGE)),
+        }
+    }
+
+    if options.configs.is_empty() {
+        options.configs.push(GameConfig::default());
+    }
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("无效的数字: {}", text))
+}
+
+fn main() {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    let mut results = Vec::new();
+    for config in &options.configs {
+        eprintln!(
+            "模拟配置 {},{},{} ({} 局, 策略 {})",
+            config.simple_block_chance,
+            config.standard_block_chance,
+            config.blocks_per_generation,
+            options.games,
+            options.strength.id()
+        );
+        let records: Vec<GameRecord> = (0..options.games as u64)
+            .map(|i| play_game(*config, options.strength, options.seed + i, options.max_moves))
+            .collect();
+        let summary = Summary::from_records(*config, options.strength, &records);
+        results.push((summary, records));
+    }
+
+    let output = if options.json {
+        format_json(&results, options.per_game)
+    } else if options.per_game {
+        format_games_csv(&results)
+    } else {
+        format_summary_csv(&results)
+    };
+
+    let written = match &options.out {
+        Some(path) => std::fs::write(path, output),
+        None => std::io::stdout().write_all(output.as_bytes()),
+    };
+    if let Err(err) = written {
+        eprintln!("写入结果失败: {}", err);
+        std::process::exit(1);
+    }
+}
+
+// 百分位列，汇总表和JSON共用
+const PERCENTILES: [u32; 5] = [10, 25, 50, 75, 90];
+
+fn format_summary_csv(results: &[(Summary, Vec<GameRecord>)]) -> String {
+    let mut out = String::from("simple_chance,standard_chance,blocks_per_generation,policy,games,mean_score,min_score");
+    for p in PERCENTILES {
+        let _ = write!(out, ",p{}_score", p);
+    }
+    out.push_str(",max_score,mean_moves,no_fit,move_limit");
+    if let Some((summary, _)) = results.first() {
+        for (name, _) in &summary.killer_pieces {
+            let _ = write!(out, ",killer_{}", name);
+        }
+    }
+    out.push('\n');
+
+    for (summary, _) in results {
+        let config = &summary.config;
+        let _ = write!(
+            out,
+            "{},{},{},{},{},{:.1},{}",
+            config.simple_block_chance,
+            config.standard_block_chance,
+            config.blocks_per_generation,
+            summary.strength.id(),
+            summary.scores.len(),
+            summary.mean_score,
+            summary.percentile(0)
+        );
+        for p in PERCENTILES {
+            let _ = write!(out, ",{}", summary.percentile(p));
+        }
+        let _ = write!(
+            out,
+            ",{},{:.1},{},{}",
+            summary.percentile(100),
+            summary.mean_moves,
+            summary.no_fit,
+            summary.move_limit
+        );
+        for (_, count) in &summary.killer_pieces {
+            let _ = write!(out, ",{}", count);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn format_games_csv(results: &[(Summary, Vec<GameRecord>)]) -> String {
+    let mut out = String::from(
+        "simple_chance,standard_chance,blocks_per_generation,policy,seed,score,moves,lines_cleared,max_combo,cause,killer_pieces\n",
+    );
+    for (summary, records) in results {
+        let config = &summary.config;
+        for record in records {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                config.simple_block_chance,
+                config.standard_block_chance,
+                config.blocks_per_generation,
+                summary.strength.id(),
+                record.seed,
+                record.score,
+                record.moves,
+                record.lines_cleared,
+                record.max_combo,
+                record.cause.name(),
+                record.killer_pieces.join(";")
+            );
+        }
+    }
+    out
+}
+
+// 手写JSON输出，字段都是数字或不含特殊字符的形状名称
+fn format_json(results: &[(Summary, Vec<GameRecord>)], per_game: bool) -> String {
+    let mut out = String::from("[\n");
+    for (i, (summary, records)) in results.iter().enumerate() {
+        let config = &summary.config;
+        out.push_str("  {\n");
+        let _ = writeln!(
+            out,
+            "    \"config\": {{\"simple_chance\": {}, \"standard_chance\": {}, \"blocks_per_generation\": {}}},",
+            config.simple_block_chance, config.standard_block_chance, config.blocks_per_generation
+        );
+        let _ = writeln!(out, "    \"policy\": \"{}\",", summary.strength.id());
+        let _ = writeln!(out, "    \"games\": {},", summary.scores.len());
+        let _ = writeln!(out, "    \"mean_score\": {:.1},", summary.mean_score);
+        let _ = writeln!(out, "    \"mean_moves\": {:.1},", summary.mean_moves);
+
+        let percentiles: Vec<String> = [0].iter().chain(PERCENTILES.iter()).chain([100].iter())
+            .map(|&p| format!("\"p{}\": {}", p, summary.percentile(p)))
+            .collect();
+        let _ = writeln!(out, "    \"score_percentiles\": {{{}}},", percentiles.join(", "));
+        let scores: Vec<String> = summary.scores.iter().map(u32::to_string).collect();
+        let _ = writeln!(out, "    \"scores\": [{}],", scores.join(", "));
+        let _ = writeln!(
+            out,
+            "    \"game_over_causes\": {{\"no_fit\": {}, \"move_limit\": {}}},",
+            summary.no_fit, summary.move_limit
+        );
+        let killers: Vec<String> = summary.killer_pieces.iter()
+            .map(|(name, count)| format!("\"{}\": {}", name, count))
+            .collect();
+        let _ = write!(out, "    \"killer_pieces\": {{{}}}", killers.join(", "));
+
+        if per_game {
+            out.push_str(",\n    \"records\": [\n");
+            for (j, record) in records.iter().enumerate() {
+                let killers: Vec<String> = record.killer_pieces.iter().map(|name| format!("\"{}\"", name)).collect();
+                let _ = write!(
+                    out,
+                    "      {{\"seed\": {}, \"score\": {}, \"moves\": {}, \"lines_cleared\": {}, \"max_combo\": {}, \"cause\": \"{}\", \"killer_pieces\": [{}]}}",
+                    record.seed,
+                    record.score,
+                    record.moves,
+                    record.lines_cleared,
+                    record.max_combo,
+                    record.cause.name(),
+                    killers.join(", ")
+                );
+                out.push_str(if j + 1 < records.len() { ",\n" } else { "\n" });
+            }
+            out.push_str("    ]");
+        }
+        out.push_str(if i + 1 < results.len() { "\n  },\n" } else { "\n  }\n" });
+    }
+    out.push_str("]\n");
+    out
+}
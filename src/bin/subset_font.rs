@@ -0,0 +1,110 @@
+// 生成语言字体的子集：每个字符串表用 _font 指定字体，子集只保留使用该字体的字符串表中的字符和可打印ASCII字符
+// 用法：cargo run --bin subset_font [-- --check]
+// 修改 resources/locales 下的字符串表后运行一次，--check 只检查子集是否缺字，缺字或缺少字体时返回非0
+// 完整字体放在 resources/fonts/<字体名称>.ttf，子集写入 resources/fonts/<字体名称>.subset.ttf
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::process::ExitCode;
+
+use block_blast::font_subset;
+
+const FONT_DIR: &str = "resources/fonts";
+const LOCALE_DIR: &str = "resources/locales";
+
+// 字体名称 -> 使用这个字体的字符串表中的所有字符（键为 _font 的行指定字体）
+fn locale_chars() -> Result<BTreeMap<String, BTreeSet<char>>, String> {
+    let mut fonts: BTreeMap<String, BTreeSet<char>> = BTreeMap::new();
+    let mut entries: Vec<_> = fs::read_dir(LOCALE_DIR)
+        .map_err(|err| format!("无法读取 {}: {}", LOCALE_DIR, err))?
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    for path in entries {
+        let text = fs::read_to_string(&path).map_err(|err| format!("无法读取 {}: {}", path.display(), err))?;
+        let values: Vec<(&str, &str)> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+        let Some(&(_, font)) = values.iter().find(|&&(key, _)| key == "_font") else {
+            continue;
+        };
+        let chars = fonts.entry(font.to_string()).or_default();
+        for (key, value) in values {
+            if !key.starts_with('_') {
+                chars.extend(value.chars().filter(|c| !c.is_whitespace()));
+            }
+        }
+        // 分数等数字和英文文本
+        chars.extend((0x20u8..0x7F).map(char::from));
+        println!("字符串表: {} -> {}", path.display(), font);
+    }
+    Ok(fonts)
+}
+
+// 检查子集是否包含所有字符，完整字体本身没有的字符不算缺字
+fn check(name: &str, chars: &BTreeSet<char>) -> Result<(), String> {
+    let full_path = format!("{}/{}.ttf", FONT_DIR, name);
+    let subset_path = format!("{}/{}.subset.ttf", FONT_DIR, name);
+    let existing = fs::read(&subset_path)
+        .map(|data| font_subset::char_set(&data))
+        .map_err(|_| format!("缺少子集字体 {}（需要先把完整字体放到 {} 再生成）", subset_path, full_path))?;
+    let available = fs::read(&full_path).map(|data| font_subset::char_set(&data)).ok();
+    let missing: String = chars
+        .iter()
+        .filter(|c| **c != ' ' && !existing.contains(c))
+        .filter(|c| available.as_ref().is_none_or(|set| set.contains(c)))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("子集字体 {} 缺少字符: {}", subset_path, missing));
+    }
+    println!("{} 包含所有 {} 个字符", subset_path, chars.len());
+    Ok(())
+}
+
+fn generate(name: &str, chars: &BTreeSet<char>) -> Result<(), String> {
+    let full_path = format!("{}/{}.ttf", FONT_DIR, name);
+    let subset_path = format!("{}/{}.subset.ttf", FONT_DIR, name);
+    let full = fs::read(&full_path).map_err(|err| format!("无法读取 {}: {}", full_path, err))?;
+    let available = font_subset::char_set(&full);
+    let missing: String = chars.iter().filter(|c| **c != ' ' && !available.contains(c)).collect();
+    if !missing.is_empty() {
+        println!("警告: {} 中没有这些字符: {}", full_path, missing);
+    }
+
+    let subset = font_subset::subset(&full, chars).ok_or(format!("无法解析字体 {}", full_path))?;
+    fs::write(&subset_path, &subset).map_err(|err| format!("无法写入 {}: {}", subset_path, err))?;
+    println!("{} 个字符，{} -> {} 字节，已写入 {}", chars.len(), full.len(), subset.len(), subset_path);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let check_only = std::env::args().any(|arg| arg == "--check");
+    let fonts = match locale_chars() {
+        Ok(fonts) => fonts,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // 每个字体分别处理，一个字体出错时继续处理其余的字体
+    let mut failed = false;
+    for (name, chars) in &fonts {
+        let result = if check_only { check(name, chars) } else { generate(name, chars) };
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            failed = true;
+        }
+    }
+    if failed {
+        if check_only {
+            eprintln!("请运行 cargo run --bin subset_font 重新生成");
+        }
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -1,11 +1,14 @@
 // 方块模块，包含方块形状定义和生成逻辑
-use macroquad::prelude::*;
-// 不再需要使用我们的自定义随机数生成器
+use crate::random::SimpleRandom;
+
+// 方块可用的颜色数量，实际颜色由主题的调色板决定
+pub const PALETTE_SIZE: usize = 7;
 
 // 定义不同形状的方块
+#[derive(Clone)]
 pub struct BlockShape {
     pub cells: Vec<(i32, i32)>,
-    pub color: Color,
+    pub color: u8,  // 调色板索引
 }
 
 impl BlockShape {
@@ -32,51 +35,59 @@ impl BlockShape {
         ]
     }
     
-    // 随机生成一个方块(原始实现)
-    pub fn random() -> Self {
-        let shapes = Self::get_all_shapes();
-        let shape_idx = macroquad::rand::gen_range(0, shapes.len() as i32);
-        
-        // 随机选择颜色
-        let colors = [RED, GREEN, BLUE, YELLOW, PURPLE, PINK, ORANGE];
-        let color_idx = macroquad::rand::gen_range(0, colors.len() as i32);
-        
-        BlockShape {
-            cells: shapes[shape_idx as usize].clone(),
-            color: colors[color_idx as usize],
-        }
-    }
-    
-    // 根据自定义概率随机生成一个方块
-    pub fn random_with_chances(simple_chance: i32, standard_chance: i32) -> Self {
+    // 使用指定的随机数生成器按概率生成方块，相同种子得到相同的方块序列
+    pub fn random_with_chances_rng(rng: &mut SimpleRandom, simple_chance: i32, standard_chance: i32) -> Self {
         let shapes = Self::get_all_shapes();
         
         // 确保概率有效(在0-100之间)
-        let simple_chance = simple_chance.max(0).min(100);
-        let standard_chance = standard_chance.max(0).min(100);
+        let simple_chance = simple_chance.clamp(0, 100);
+        let standard_chance = standard_chance.clamp(0, 100);
         let _complex_chance = 100 - simple_chance - standard_chance;
         
         // 根据概率选择形状类别
-        let category_roll = macroquad::rand::gen_range(0, 100);
+        let category_roll = rng.gen_range(0, 100);
         
         let shape_idx = if category_roll < simple_chance {
             // 选择简单形状(索引0-2)
-            macroquad::rand::gen_range(0, 3)
+            rng.gen_range(0, 3)
         } else if category_roll < simple_chance + standard_chance {
             // 选择标准形状(索引3-7)
-            macroquad::rand::gen_range(3, 8)
+            rng.gen_range(3, 8)
         } else {
             // 选择复杂形状(索引8-11)
-            macroquad::rand::gen_range(8, 12)
+            rng.gen_range(8, 12)
         };
         
         // 随机选择颜色
-        let colors = [RED, GREEN, BLUE, YELLOW, PURPLE, PINK, ORANGE];
-        let color_idx = macroquad::rand::gen_range(0, colors.len() as i32);
+        let color_idx = rng.gen_range(0, PALETTE_SIZE as i32);
         
         BlockShape {
             cells: shapes[shape_idx as usize].clone(),
-            color: colors[color_idx as usize],
+            color: color_idx as u8,
         }
     }
-} 
\ No newline at end of file
+    
+    // 按形状名称（见SHAPE_NAMES）和调色板索引构造方块，名称未知时返回None
+    pub fn from_name(name: &str, color: u8) -> Option<Self> {
+        let idx = SHAPE_NAMES.iter().position(|&n| n == name)?;
+        Some(BlockShape {
+            cells: Self::get_all_shapes().swap_remove(idx),
+            color,
+        })
+    }
+    
+    // 形状名称，用于统计和调试输出
+    pub fn shape_name(&self) -> &'static str {
+        Self::get_all_shapes()
+            .iter()
+            .position(|cells| *cells == self.cells)
+            .map_or("custom", |idx| SHAPE_NAMES[idx])
+    }
+}
+
+// 与get_all_shapes中的形状一一对应
+pub const SHAPE_NAMES: [&str; 12] = [
+    "single", "domino_h", "domino_v",
+    "I", "O", "L", "T", "Z",
+    "big_L", "big_L_rev", "step", "cross",
+];
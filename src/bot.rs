@@ -0,0 +1,295 @@
+// 自动游玩机器人，用于菜单吸引模式演示和长时间稳定性测试
+use crate::block::BlockShape;
+use crate::grid::Grid;
+use crate::random::SimpleRandom;
+
+/// 机器人强度
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BotStrength {
+    Random,     // 在所有合法位置中随机选择
+    Greedy,     // 只看当前这一步的最佳结果
+    Lookahead,  // 额外考虑剩余方块的下一步
+}
+
+impl BotStrength {
+    /// 切换到下一个强度
+    pub fn next(self) -> Self {
+        match self {
+            BotStrength::Random => BotStrength::Greedy,
+            BotStrength::Greedy => BotStrength::Lookahead,
+            BotStrength::Lookahead => BotStrength::Random,
+        }
+    }
+
+    /// 从命令行名称解析（random / greedy / lookahead）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(BotStrength::Random),
+            "greedy" => Some(BotStrength::Greedy),
+            "lookahead" => Some(BotStrength::Lookahead),
+            _ => None,
+        }
+    }
+
+    /// 命令行和统计输出使用的英文名称
+    pub fn id(self) -> &'static str {
+        match self {
+            BotStrength::Random => "random",
+            BotStrength::Greedy => "greedy",
+            BotStrength::Lookahead => "lookahead",
+        }
+    }
+
+    /// 显示名称
+    pub fn name(self) -> &'static str {
+        match self {
+            BotStrength::Random => "随机",
+            BotStrength::Greedy => "贪心",
+            BotStrength::Lookahead => "前瞻",
+        }
+    }
+}
+
+/// 一次放置操作：方块索引和左上角网格坐标
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BotMove {
+    pub block_idx: usize,
+    pub grid_x: i32,
+    pub grid_y: i32,
+}
+
+// 从对局种子派生机器人种子时使用的常数（黄金分割比的64位定点表示）
+const BOT_SEED_SALT: u64 = 0x9E37_79B9_7F4A_7C15;
+
+pub struct Bot {
+    pub strength: BotStrength,
+    rng: SimpleRandom,
+}
+
+impl Bot {
+    pub fn new(strength: BotStrength, seed: u64) -> Self {
+        Bot {
+            strength,
+            rng: SimpleRandom::new(seed),
+        }
+    }
+
+    /// 为用game_seed开始的对局创建机器人，机器人的随机数由对局种子派生，
+    /// 避免和方块生成使用同一个随机序列
+    pub fn for_game(strength: BotStrength, game_seed: u64) -> Self {
+        Bot::new(strength, game_seed ^ BOT_SEED_SALT)
+    }
+
+    /// 为当前局面选择一步，没有可放置位置时返回None
+    pub fn choose_move(&mut self, grid: &Grid, blocks: &[BlockShape]) -> Option<BotMove> {
+        let moves = legal_moves(grid, blocks);
+        if moves.is_empty() {
+            return None;
+        }
+
+        match self.strength {
+            BotStrength::Random => self.rng.choose(&moves).copied(),
+            BotStrength::Greedy => best_move(&moves, |m| {
+                let (after, cleared) = simulate(grid, &blocks[m.block_idx], m);
+                cleared as f32 * LINE_WEIGHT + evaluate_board(&after)
+            }),
+            BotStrength::Lookahead => best_move(&moves, |m| {
+                let (after, cleared) = simulate(grid, &blocks[m.block_idx], m);
+                cleared as f32 * LINE_WEIGHT + best_follow_up(&after, blocks, m.block_idx)
+            }),
+        }
+    }
+}
+
+// 每消除一条线的奖励权重
+const LINE_WEIGHT: f32 = 20.0;
+
+// 下一步无处可放时的惩罚
+const DEAD_END_PENALTY: f32 = -1000.0;
+
+/// 列出所有合法放置
+pub fn legal_moves(grid: &Grid, blocks: &[BlockShape]) -> Vec<BotMove> {
+    let mut moves = Vec::new();
+    for (block_idx, block) in blocks.iter().enumerate() {
+        for grid_y in 0..8 {
+            for grid_x in 0..8 {
+                if grid.can_place_block(block, grid_x, grid_y) {
+                    moves.push(BotMove { block_idx, grid_x, grid_y });
+                }
+            }
+        }
+    }
+    moves
+}
+
+// 在副本网格上模拟放置并消除，返回结果网格和消除的线数
+fn simulate(grid: &Grid, block: &BlockShape, m: &BotMove) -> (Grid, u32) {
+    let mut after = grid.clone();
+    after.place_block(block, m.grid_x, m.grid_y);
+    let (rows, cols) = after.check_and_clear();
+    (after, rows + cols)
+}
+
+// 选出评分最高的一步（评分相同时保留先找到的）
+fn best_move(moves: &[BotMove], mut score: impl FnMut(&BotMove) -> f32) -> Option<BotMove> {
+    let mut best: Option<(BotMove, f32)> = None;
+    for m in moves {
+        let value = score(m);
+        if best.is_none_or(|(_, best_value)| value > best_value) {
+            best = Some((*m, value));
+        }
+    }
+    best.map(|(m, _)| m)
+}
+
+// 剩余方块中下一步能达到的最佳评分
+fn best_follow_up(grid: &Grid, blocks: &[BlockShape], used_idx: usize) -> f32 {
+    let mut best: Option<f32> = None;
+    let mut has_remaining = false;
+    for (block_idx, block) in blocks.iter().enumerate() {
+        if block_idx == used_idx {
+            continue;
+        }
+        has_remaining = true;
+        for grid_y in 0..8 {
+            for grid_x in 0..8 {
+                if !grid.can_place_block(block, grid_x, grid_y) {
+                    continue;
+                }
+                let m = BotMove { block_idx, grid_x, grid_y };
+                let (after, cleared) = simulate(grid, block, &m);
+                let value = cleared as f32 * LINE_WEIGHT + evaluate_board(&after);
+                if best.is_none_or(|b| value > b) {
+                    best = Some(value);
+                }
+            }
+        }
+    }
+
+    match best {
+        Some(value) => value,
+        // 这是最后一个方块，之后会生成新方块，只评估当前局面
+        None if !has_remaining => evaluate_board(grid),
+        None => DEAD_END_PENALTY,
+    }
+}
+
+/// 局面评估：空格越多越好，孤立空洞和凹凸越少越好
+pub fn evaluate_board(grid: &Grid) -> f32 {
+    let filled = |x: i32, y: i32| {
+        !(0..8).contains(&x) || !(0..8).contains(&y) || grid.cells[y as usize][x as usize].is_some()
+    };
+
+    let mut empty = 0;
+    let mut holes = 0;
+    let mut transitions = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            if !filled(x, y) {
+                empty += 1;
+                // 四周都被占据的空格很难再填上
+                if filled(x - 1, y) && filled(x + 1, y) && filled(x, y - 1) && filled(x, y + 1) {
+                    holes += 1;
+                }
+            }
+            // 统计行列方向上的占用状态变化
+            if filled(x, y) != filled(x + 1, y) {
+                transitions += 1;
+            }
+            if filled(x, y) != filled(x, y + 1) {
+                transitions += 1;
+            }
+        }
+    }
+
+    empty as f32 - holes as f32 * 4.0 - transitions as f32 * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{GameConfig, GameEngine};
+
+    const STRENGTHS: [BotStrength; 3] = [BotStrength::Random, BotStrength::Greedy, BotStrength::Lookahead];
+
+    // 机器人玩一局，返回每一步的选择
+    fn play(strength: BotStrength, seed: u64, max_moves: u32) -> Vec<(usize, i32, i32)> {
+        let mut engine = GameEngine::new(GameConfig::default(), seed);
+        let mut bot = Bot::for_game(strength, seed);
+        let mut moves = Vec::new();
+        while engine.moves < max_moves {
+            let Some(m) = bot.choose_move(&engine.grid, &engine.current_blocks) else { break };
+            assert!(engine.can_place(m.block_idx, m.grid_x, m.grid_y), "{:?} 选择了不合法的位置", strength);
+            assert!(engine.place(m.block_idx, m.grid_x, m.grid_y).is_some());
+            moves.push((m.block_idx, m.grid_x, m.grid_y));
+        }
+        moves
+    }
+
+    #[test]
+    fn chosen_moves_are_legal() {
+        for strength in STRENGTHS {
+            for seed in 0..2 {
+                assert!(!play(strength, seed, 30).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_chooses_same_moves() {
+        for strength in STRENGTHS {
+            assert_eq!(play(strength, 9, 20), play(strength, 9, 20));
+        }
+        // 随机策略的选择来自机器人种子
+        let grid = Grid::new();
+        let blocks = [BlockShape::from_name("single", 0).unwrap()];
+        let picks = |seed| {
+            let mut bot = Bot::for_game(BotStrength::Random, seed);
+            (0..8).map(|_| bot.choose_move(&grid, &blocks).unwrap()).map(|m| (m.grid_x, m.grid_y)).collect::<Vec<_>>()
+        };
+        assert_eq!(picks(5), picks(5));
+        assert_ne!(picks(5), picks(6));
+    }
+
+    #[test]
+    fn no_move_when_nothing_fits() {
+        let mut grid = Grid::new();
+        for row in grid.cells.iter_mut() {
+            *row = [Some(0); 8];
+        }
+        let blocks = [BlockShape::from_name("single", 0).unwrap()];
+        for strength in STRENGTHS {
+            assert!(legal_moves(&grid, &blocks).is_empty());
+            assert!(Bot::new(strength, 1).choose_move(&grid, &blocks).is_none());
+        }
+    }
+
+    #[test]
+    fn greedy_completes_a_line() {
+        let mut grid = Grid::new();
+        for x in 0..7 {
+            grid.cells[7][x] = Some(1);
+        }
+        let blocks = [BlockShape::from_name("single", 0).unwrap()];
+        for strength in [BotStrength::Greedy, BotStrength::Lookahead] {
+            let m = Bot::new(strength, 1).choose_move(&grid, &blocks).unwrap();
+            assert_eq!((m.grid_x, m.grid_y), (7, 7));
+        }
+    }
+
+    #[test]
+    fn evaluation_penalizes_holes() {
+        let empty = evaluate_board(&Grid::new());
+        let mut hole = Grid::new();
+        for (x, y) in [(3, 2), (2, 3), (4, 3), (3, 4)] {
+            hole.cells[y][x] = Some(0);
+        }
+        let mut block = Grid::new();
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            block.cells[y][x] = Some(0);
+        }
+        assert!(empty > evaluate_board(&block));
+        // 同样占4格，围出空洞的局面更差
+        assert!(evaluate_board(&block) > evaluate_board(&hole));
+    }
+}
@@ -0,0 +1,69 @@
+// 显示缩放：像素比（帧缓冲像素/逻辑像素）和界面缩放
+// 像素比用于按物理像素光栅化文字，使高DPI屏幕上的文字清晰；界面缩放用于文字、线宽等界面元素的大小
+// 界面缩放 = 玩家设置的缩放 × 网页上设备像素比变化的补偿
+// miniquad在网页上只在启动时读取一次devicePixelRatio，之后缩放浏览器或把窗口移到另一台显示器时，
+// 逻辑像素不再等于CSS像素，由补偿让界面元素保持原来的显示大小
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+use macroquad::prelude::*;
+
+/// 设置中可选的界面缩放
+pub const UI_SCALES: [f32; 6] = [0.8, 0.9, 1.0, 1.1, 1.25, 1.5];
+
+struct DisplayScale {
+    pixel_ratio: f32,      // miniquad报告的像素比
+    device_ratio: f32,     // 网页上实时的devicePixelRatio，原生平台与pixel_ratio相同
+    user_scale: f32,       // 玩家设置的界面缩放
+}
+
+static SCALE: Lazy<Mutex<DisplayScale>> = Lazy::new(|| {
+    Mutex::new(DisplayScale { pixel_ratio: 1.0, device_ratio: 1.0, user_scale: 1.0 })
+});
+
+// 由 block_blast_bridge.js 提供
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn bb_device_pixel_ratio() -> f32;
+}
+
+fn query_device_ratio(pixel_ratio: f32) -> f32 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let ratio = unsafe { bb_device_pixel_ratio() };
+        if ratio > 0.0 {
+            return ratio;
+        }
+    }
+    pixel_ratio
+}
+
+/// 重新读取像素比，每帧调用一次，返回是否发生了变化
+pub fn update() -> bool {
+    let pixel_ratio = screen_dpi_scale().max(0.1);
+    let device_ratio = query_device_ratio(pixel_ratio);
+    let mut scale = SCALE.lock().unwrap();
+    let changed = (scale.pixel_ratio - pixel_ratio).abs() > 0.01 || (scale.device_ratio - device_ratio).abs() > 0.01;
+    if changed {
+        println!("像素比变化: {} (设备像素比 {})", pixel_ratio, device_ratio);
+        scale.pixel_ratio = pixel_ratio;
+        scale.device_ratio = device_ratio;
+    }
+    changed
+}
+
+/// 每个逻辑像素对应的帧缓冲像素数
+pub fn pixel_ratio() -> f32 {
+    SCALE.lock().unwrap().pixel_ratio
+}
+
+/// 界面元素的缩放，文字大小和线宽乘以该值
+pub fn ui_scale() -> f32 {
+    let scale = SCALE.lock().unwrap();
+    scale.user_scale * scale.device_ratio / scale.pixel_ratio
+}
+
+/// 设置玩家选择的界面缩放
+pub fn set_user_scale(user_scale: f32) {
+    SCALE.lock().unwrap().user_scale = user_scale;
+}
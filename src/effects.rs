@@ -1,172 +1,438 @@
 // 音效和特效
 use macroquad::prelude::*;
 
-// 单个粒子的定义
-pub struct Particle {
+use crate::audio::Sounds;
+use crate::grid::Grid;
+use crate::locale::{tr, tr_args, tr_count};
+use crate::particles::{EmitMode, EmitterDesc, ParticleShape, ParticleSystem};
+use crate::random::SimpleRandom;
+use crate::render::{DrawList, Layer};
+use crate::text::TextStyle;
+use crate::theme::{self, Theme};
+use crate::tween::Easing;
+
+// 消除动画：格子沿行（从左到右）或列（从上到下）依次闪白，然后缩小消失
+const CLEAR_SWEEP_STEP: f32 = 0.03;   // 相邻格子开始动画的间隔（秒）
+const CLEAR_FLASH_TIME: f32 = 0.12;
+const CLEAR_SHRINK_TIME: f32 = 0.2;
+
+// 正在播放消除动画的格子
+// 规则引擎在放置时已经清除了这些格子，这里只保留调色板索引用于绘制过渡
+struct ClearingCell {
+    x: usize,
+    y: usize,
+    color: u8,
+    time: f32,  // 从负的延迟开始计时，到0时开始闪白
+}
+
+// 飘字动画：得分向上飘并淡出；横幅弹出放大后停留再淡出
+const SCORE_POPUP_TIME: f32 = 0.9;
+const BANNER_TIME: f32 = 1.2;
+const BANNER_SCALE_IN_TIME: f32 = 0.25;
+
+// 震屏和闪屏：大消除时画面抖动并短暂闪白
+const MAX_SHAKE: f32 = 12.0;        // 最大抖动幅度（像素）
+const SHAKE_DECAY: f32 = 1.6;       // 抖动强度每秒衰减量
+const FLASH_DECAY: f32 = 2.5;       // 闪屏透明度每秒衰减量
+
+// 特效按固定步长推进，与帧率无关：同样的种子和同样的帧时间序列得到完全相同的画面
+pub const EFFECT_STEP: f32 = 1.0 / 120.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum PopupStyle {
+    Float,
+    Banner,
+}
+
+// 屏幕上的一段文字（得分、连击横幅、夸奖）
+struct Popup {
+    text: String,
     position: Vec2,
-    velocity: Vec2,
     color: Color,
-    size: f32,
+    font_size: f32,
+    age: f32,
     lifetime: f32,
-    max_lifetime: f32,
+    style: PopupStyle,
+    is_combo: bool,  // 连击横幅，新的连击横幅替换旧的
 }
 
-impl Particle {
-    pub fn new(position: Vec2, velocity: Vec2, color: Color, size: f32, lifetime: f32) -> Self {
-        Particle {
-            position,
-            velocity,
-            color,
-            size,
-            lifetime,
-            max_lifetime: lifetime,
-        }
-    }
-    
-    // 更新粒子状态
-    pub fn update(&mut self, dt: f32) -> bool {
-        self.position += self.velocity * dt;
-        self.lifetime -= dt;
-        self.velocity.y += 50.0 * dt; // 简单重力效果
+impl Popup {
+    fn draw(&self, list: &mut DrawList, text_scale: f32) {
+        let progress = self.age / self.lifetime;
+        // 最后40%的时间淡出
+        let alpha = ((1.0 - progress) / 0.4).min(1.0);
+        let color = Color::new(self.color.r, self.color.g, self.color.b, self.color.a * alpha);
         
-        // 返回粒子是否存活
-        self.lifetime > 0.0
-    }
-    
-    // 绘制粒子
-    pub fn draw(&self) {
-        // 随着生命周期减少透明度
-        let alpha = self.lifetime / self.max_lifetime;
-        let draw_color = Color::new(
-            self.color.r,
-            self.color.g,
-            self.color.b,
-            self.color.a * alpha
-        );
-        
-        draw_rectangle(
-            self.position.x, 
-            self.position.y, 
-            self.size * alpha, // 随时间缩小
-            self.size * alpha,
-            draw_color
-        );
+        let (offset_y, scale) = match self.style {
+            PopupStyle::Float => (-40.0 * Easing::QuadOut.apply(progress), 1.0),
+            PopupStyle::Banner => (0.0, Easing::BackOut.apply(self.age / BANNER_SCALE_IN_TIME)),
+        };
+        let font_size = self.font_size * text_scale * scale;
+        // 深色描边让飘字在彩色方块上也清晰可读
+        let outline = Color::new(0.0, 0.0, 0.0, 0.6 * alpha);
+        let style = TextStyle::new(font_size, color).outline((font_size * 0.06).max(1.0), outline);
+        list.text(&self.text, self.position + Vec2::new(0.0, offset_y), style);
     }
 }
 
-pub struct ParticleSystem {
-    particles: Vec<Particle>,
-}
-
-impl ParticleSystem {
-    pub fn new() -> Self {
-        ParticleSystem { 
-            particles: Vec::new()
-        }
-    }
-    
-    // 在指定位置创建消除效果
-    pub fn create_clear_effect(&mut self, position: Vec2, color: Color, count: i32) {
-        for _ in 0..count {
-            // 随机方向
-            let angle = macroquad::rand::gen_range(0.0, std::f32::consts::PI * 2.0);
-            let speed = macroquad::rand::gen_range(30.0, 80.0);
-            let velocity = Vec2::new(
-                angle.cos() * speed,
-                angle.sin() * speed
-            );
-            
-            // 随机大小
-            let size = macroquad::rand::gen_range(3.0, 8.0);
-            
-            // 随机生命周期
-            let lifetime = macroquad::rand::gen_range(0.5, 1.5);
-            
-            // 创建新粒子
-            let particle = Particle::new(
-                position,
-                velocity,
-                color,
-                size,
-                lifetime
-            );
-            
-            self.particles.push(particle);
-        }
-    }
-    
-    // 更新所有粒子
-    pub fn update(&mut self, dt: f32) {
-        // 反向迭代以便安全移除元素
-        let mut i = self.particles.len();
-        while i > 0 {
-            i -= 1;
-            
-            // 更新粒子，如果返回false表示粒子生命周期结束
-            if !self.particles[i].update(dt) {
-                self.particles.swap_remove(i);
-            }
-        }
-    }
-    
-    // 绘制所有粒子
-    pub fn draw(&self) {
-        for particle in &self.particles {
-            particle.draw();
-        }
-    }
-    
-    // 当前粒子数量
-    pub fn count(&self) -> usize {
-        self.particles.len()
+// 一次消除多行时的夸奖，按消除行数选择
+fn praise_word(lines: u32) -> Option<&'static str> {
+    match lines {
+        0 | 1 => None,
+        2 => Some("praise.2"),
+        3 => Some("praise.3"),
+        4 => Some("praise.4"),
+        _ => Some("praise.5"),
     }
 }
 
 pub struct Effects {
     // 游戏中使用的音效和特效
     pub particles: ParticleSystem,
+    pub sounds: Sounds,  // 启动时异步加载，加载前不播放任何声音
+    clearing: Vec<ClearingCell>,
+    popups: Vec<Popup>,
+    pub text_scale: f32,  // 飘字的字体缩放（DPI）
+    shake: f32,           // 震屏强度，0.0-1.0，幅度按平方变化
+    shake_offset: Vec2,   // 本帧的绘制原点偏移
+    flash: f32,           // 闪屏透明度
+    pub reduce_motion: bool,  // 无障碍设置：关闭震屏和闪屏
+    rng: SimpleRandom,    // 特效专用的随机数，与玩法的随机序列互不影响
+    time: f64,            // 特效时钟（秒），只按固定步长前进，闪烁等周期动画用它代替 get_time()
+    accumulator: f32,     // 还没推进的帧时间，不足一步的部分留到下一帧
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Effects {
     pub fn new() -> Self {
         Effects {
-            particles: ParticleSystem::new()
+            particles: ParticleSystem::new(),
+            sounds: Sounds::default(),
+            clearing: Vec::new(),
+            popups: Vec::new(),
+            text_scale: 1.0,
+            shake: 0.0,
+            shake_offset: Vec2::ZERO,
+            flash: 0.0,
+            reduce_motion: false,
+            rng: SimpleRandom::new(0),
+            time: 0.0,
+            accumulator: 0.0,
         }
     }
     
     pub fn play_place_sound(&self) {
         // 播放放置方块的音效
+        self.sounds.play_place();
+    }
+    
+    pub fn play_clear_sound(&self, count: u32, combo: u32) {
+        // 播放消除的音效，根据消除数量和连击数调整
+        self.sounds.play_clear(count, combo);
     }
     
-    pub fn play_clear_sound(&self, _count: u32) {
-        // 播放消除的音效，根据消除数量调整
+    pub fn play_game_over_sound(&self) {
+        self.sounds.play_game_over();
     }
     
     pub fn show_clear_effect(&mut self, x: f32, y: f32, color: Color) {
-        // 显示消除特效
+        // 显示消除特效：方块颜色的碎片向四周散开并下落，中间夹杂叠加混合的亮点
         let position = Vec2::new(x, y);
-        // 生成20-40个粒子
-        let count = macroquad::rand::gen_range(20, 40);
-        self.particles.create_clear_effect(position, color, count);
+        let spark = theme::current().spark;
+        let debris = EmitterDesc {
+            mode: EmitMode::Burst { min: 8, max: 14 },
+            speed: (40.0, 110.0),
+            lifetime: (0.5, 1.2),
+            size: (4.0, 9.0),
+            spin: (-6.0, 6.0),
+            gravity: 160.0,
+            drag: 1.5,
+            color_start: color,
+            color_end: Color::new(color.r, color.g, color.b, 0.0),
+            ..Default::default()
+        };
+        let sparks = EmitterDesc {
+            mode: EmitMode::Burst { min: 3, max: 5 },
+            shape: ParticleShape::Circle,
+            speed: (20.0, 60.0),
+            lifetime: (0.3, 0.6),
+            size: (6.0, 10.0),
+            size_easing: Easing::QuadIn,
+            gravity: 0.0,
+            drag: 3.0,
+            color_start: spark,
+            color_end: Color::new(color.r, color.g, color.b, 0.0),
+            additive: true,
+            ..Default::default()
+        };
+        self.particles.emit(&debris, position);
+        self.particles.emit(&sparks, position);
     }
     
-    pub fn show_combo_effect(&mut self, combo: u32, x: f32, y: f32) {
-        // 显示连击特效 - 连击数越高，粒子越多
-        let position = Vec2::new(x, y);
-        // 根据连击数增加粒子数量
-        let count = 10 + (combo as i32 * 5);
-        // 连击效果使用金色粒子
-        let gold_color = Color::new(1.0, 0.85, 0.0, 1.0);
-        self.particles.create_clear_effect(position, gold_color, count);
+    // 连击星星：持续喷出一小段时间，连击数越高越密集，颜色来自主题
+    fn emit_combo_stars(&mut self, combo: u32, x: f32, y: f32) {
+        let theme = theme::current();
+        let stars = EmitterDesc {
+            mode: EmitMode::Continuous { rate: 30.0 + combo as f32 * 15.0, duration: 0.4 },
+            shape: ParticleShape::Star,
+            direction: -std::f32::consts::FRAC_PI_2,
+            spread: std::f32::consts::PI,
+            speed: (80.0, 180.0),
+            lifetime: (0.6, 1.1),
+            size: (10.0, 16.0),
+            size_end: 0.2,
+            size_easing: Easing::QuadIn,
+            spin: (-4.0, 4.0),
+            gravity: 200.0,
+            drag: 0.8,
+            color_start: theme.star_start,
+            color_end: theme.star_end,
+            additive: true,
+        };
+        self.particles.emit(&stars, Vec2::new(x, y));
+    }
+    
+    // 在消除位置显示向上飘的得分
+    pub fn show_score_popup(&mut self, points: u32, x: f32, y: f32) {
+        self.popups.push(Popup {
+            text: tr_args("popup.points", &[("points", &points)]),
+            position: Vec2::new(x, y),
+            color: Color::new(1.0, 0.85, 0.2, 1.0),
+            font_size: 24.0,
+            age: 0.0,
+            lifetime: SCORE_POPUP_TIME,
+            style: PopupStyle::Float,
+            is_combo: false,
+        });
     }
     
+    // 连击横幅，替换还在显示的上一个横幅；连击3次以上时同时喷出星星
+    pub fn show_combo_banner(&mut self, combo: u32, x: f32, y: f32) {
+        if combo > 2 {
+            self.emit_combo_stars(combo - 1, x, y);
+        }
+        self.popups.retain(|popup| !popup.is_combo);
+        self.popups.push(Popup {
+            text: tr_count("popup.combo", combo as i64, &[]),
+            position: Vec2::new(x, y),
+            color: Color::new(1.0, 0.5, 0.1, 1.0),
+            font_size: 36.0,
+            age: 0.0,
+            lifetime: BANNER_TIME,
+            style: PopupStyle::Banner,
+            is_combo: true,
+        });
+    }
+    
+    // 一次消除多行时显示夸奖
+    pub fn show_praise(&mut self, lines: u32, x: f32, y: f32) {
+        let Some(word) = praise_word(lines) else { return };
+        self.popups.push(Popup {
+            text: tr(word),
+            position: Vec2::new(x, y),
+            color: Color::new(0.4, 0.9, 1.0, 1.0),
+            font_size: 30.0,
+            age: 0.0,
+            lifetime: BANNER_TIME,
+            style: PopupStyle::Banner,
+            is_combo: false,
+        });
+    }
+    
+    // 消除3行以上或高连击时震屏并闪屏，越大的消除越强烈
+    pub fn show_impact(&mut self, lines: u32, combo: u32) {
+        if self.reduce_motion || (lines < 3 && combo < 4) {
+            return;
+        }
+        let strength = 0.15 * lines.saturating_sub(1) as f32 + 0.1 * combo.saturating_sub(2) as f32;
+        self.shake = (self.shake + strength).min(1.0);
+        self.flash = (0.2 + 0.1 * strength).min(0.5);
+    }
+    
+    // 绘制原点的偏移，震屏时由 draw_game 加到网格位置上
+    pub fn shake_offset(&self) -> Vec2 {
+        self.shake_offset
+    }
+    
+    // 播放行列消除动画，board为消除前（已放下方块）的网格
+    pub fn show_line_clear(&mut self, cleared_rows: &[bool; 8], cleared_cols: &[bool; 8], board: &Grid) {
+        for (y, row) in board.cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let Some(color) = *cell else { continue };
+                // 行和列交叉的格子取较早的开始时间
+                let delay = match (cleared_rows[y], cleared_cols[x]) {
+                    (true, true) => x.min(y),
+                    (true, false) => x,
+                    (false, true) => y,
+                    (false, false) => continue,
+                } as f32 * CLEAR_SWEEP_STEP;
+                self.clearing.push(ClearingCell { x, y, color, time: -delay });
+            }
+        }
+    }
+    
+    // 开始新的一局时取消所有消除动画、粒子、飘字和震屏，并重新播种特效随机数
+    // 使用与对局相同的种子，回放同一局时特效画面也完全一致
+    pub fn reset(&mut self, seed: u64) {
+        // 与玩法随机数错开，避免两条序列相同
+        self.rng = SimpleRandom::new(seed ^ 0xeffe_c7a5_5eed_0001);
+        self.particles.reset(self.rng.next_u64());
+        self.clearing.clear();
+        self.popups.clear();
+        self.shake = 0.0;
+        self.shake_offset = Vec2::ZERO;
+        self.flash = 0.0;
+        self.time = 0.0;
+        self.accumulator = 0.0;
+    }
+    
+    // 特效时钟，开始新的一局时归零
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    
+    // 累计帧时间，按 EFFECT_STEP 固定步长推进所有特效，dt由调用方限制上限
     pub fn update(&mut self, dt: f32) {
+        self.accumulator += dt;
+        while self.accumulator >= EFFECT_STEP {
+            self.accumulator -= EFFECT_STEP;
+            self.step(EFFECT_STEP);
+        }
+    }
+    
+    fn step(&mut self, dt: f32) {
         // 更新所有特效
+        self.time += dt as f64;
         self.particles.update(dt);
+        
+        for cell in &mut self.clearing {
+            cell.time += dt;
+        }
+        self.clearing.retain(|cell| cell.time < CLEAR_FLASH_TIME + CLEAR_SHRINK_TIME);
+        
+        for popup in &mut self.popups {
+            popup.age += dt;
+        }
+        self.popups.retain(|popup| popup.age < popup.lifetime);
+        
+        self.shake = (self.shake - SHAKE_DECAY * dt).max(0.0);
+        self.flash = (self.flash - FLASH_DECAY * dt).max(0.0);
+        if self.reduce_motion {
+            self.shake = 0.0;
+            self.flash = 0.0;
+        }
+        self.shake_offset = if self.shake > 0.0 {
+            let amplitude = MAX_SHAKE * self.shake * self.shake;
+            Vec2::new(
+                self.rng.gen_range_f32(-1.0, 1.0) * amplitude,
+                self.rng.gen_range_f32(-1.0, 1.0) * amplitude
+            )
+        } else {
+            Vec2::ZERO
+        };
+    }
+    
+    // 消除动画中的方块画在方块层，闪白画在特效层，格子已被新方块占据时不再绘制
+    pub fn draw_line_clears(&self, list: &mut DrawList, grid: &Grid, origin: Vec2, cell_size: f32, theme: &Theme) {
+        for cell in &self.clearing {
+            if grid.cells[cell.y][cell.x].is_some() {
+                continue;
+            }
+            let x = origin.x + cell.x as f32 * cell_size;
+            let y = origin.y + cell.y as f32 * cell_size;
+            let rect = Rect::new(x, y, cell_size, cell_size);
+            
+            list.set_layer(Layer::Blocks);
+            if cell.time < CLEAR_FLASH_TIME {
+                // 等待和闪白阶段：保持原样，闪白时叠加逐渐变淡的白色
+                list.block(rect, cell.color, 1.0, theme);
+                if cell.time >= 0.0 {
+                    let alpha = 1.0 - cell.time / CLEAR_FLASH_TIME;
+                    list.set_layer(Layer::Effects);
+                    list.rect(rect, Color::new(1.0, 1.0, 1.0, 0.8 * alpha));
+                }
+            } else {
+                // 缩小阶段：以格子中心缩小并变透明
+                let scale = 1.0 - (cell.time - CLEAR_FLASH_TIME) / CLEAR_SHRINK_TIME;
+                let size = cell_size * scale;
+                let inset = (cell_size - size) / 2.0;
+                list.block(Rect::new(x + inset, y + inset, size, size), cell.color, scale, theme);
+            }
+        }
+    }
+    
+    // 粒子画在特效层
+    pub fn draw(&self, list: &mut DrawList) {
+        list.set_layer(Layer::Effects);
+        self.particles.draw(list);
     }
     
-    pub fn draw(&self) {
-        // 绘制所有特效
-        self.particles.draw();
+    // 飘字和覆盖整个画面的闪屏画在覆盖层
+    pub fn draw_popups(&self, list: &mut DrawList, screen: Vec2) {
+        list.set_layer(Layer::Overlay);
+        for popup in &self.popups {
+            popup.draw(list, self.text_scale);
+        }
+        if self.flash > 0.0 {
+            list.rect(Rect::new(0.0, 0.0, screen.x, screen.y), Color::new(1.0, 1.0, 1.0, self.flash));
+        }
+    }
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 一次大消除：碎片、连击星星和震屏，按给定的帧时间序列运行，记录每帧的震屏偏移
+    fn run(seed: u64, frames: &[f32]) -> (Effects, Vec<Vec2>) {
+        let mut effects = Effects::new();
+        effects.reset(seed);
+        effects.show_clear_effect(100.0, 100.0, RED);
+        effects.show_combo_banner(5, 100.0, 100.0);
+        effects.show_impact(4, 5);
+        let offsets = frames.iter().map(|&dt| {
+            effects.update(dt);
+            effects.shake_offset()
+        }).collect();
+        (effects, offsets)
+    }
+
+    #[test]
+    fn same_seed_and_frames_give_same_effects() {
+        let frames = [0.016, 0.033, 0.004, 0.05, 0.017, 0.1, 0.016];
+        let (a, offsets_a) = run(7, &frames);
+        let (b, offsets_b) = run(7, &frames);
+        assert_eq!(offsets_a, offsets_b);
+        assert_ne!(offsets_a[0], Vec2::ZERO);
+        assert_eq!(a.particles.count(), b.particles.count());
+        assert_eq!(a.time(), b.time());
+        assert_ne!(run(8, &frames).1, offsets_a);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn combo_banner_replaces_the_previous_one_and_adds_stars() {
+        let mut effects = Effects::new();
+        effects.show_combo_banner(2, 0.0, 0.0);
+        effects.update(0.1);
+        assert_eq!(effects.particles.count(), 0);
+        effects.show_combo_banner(3, 0.0, 0.0);
+        effects.update(0.1);
+        assert!(effects.particles.count() > 0);
+        assert_eq!(effects.popups.iter().filter(|popup| popup.is_combo).count(), 1);
+    }
+
+    #[test]
+    fn effects_advance_in_fixed_steps() {
+        let mut effects = Effects::new();
+        effects.update(EFFECT_STEP * 0.5);
+        assert_eq!(effects.time(), 0.0);
+        effects.update(EFFECT_STEP * 0.75);
+        assert_eq!(effects.time(), EFFECT_STEP as f64);
+        effects.reset(1);
+        assert_eq!(effects.time(), 0.0);
+    }
+}
@@ -0,0 +1,222 @@
+// 游戏规则引擎：网格、候选方块、计分和连击，不依赖窗口和绘制
+// 界面、自动游玩和模拟工具共用同一套规则
+use crate::block::BlockShape;
+use crate::grid::Grid;
+use crate::random::SimpleRandom;
+
+/// 方块生成配置
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GameConfig {
+    pub simple_block_chance: i32,     // 简单方块生成概率 (0-100)
+    pub standard_block_chance: i32,   // 标准方块生成概率 (0-100)
+    pub blocks_per_generation: usize, // 每次生成的方块数量 (1-5)
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            simple_block_chance: 30,
+            standard_block_chance: 60,
+            blocks_per_generation: 3,
+        }
+    }
+}
+
+impl GameConfig {
+    /// 解析 "简单概率,标准概率,方块数"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split(',').map(str::trim).collect();
+        if parts.len() != 3 {
+            return Err(format!("配置格式应为 简单概率,标准概率,方块数: {}", text));
+        }
+        let number = |part: &str| part.parse().map_err(|_| format!("无效的数字: {}", part));
+        let config = GameConfig {
+            simple_block_chance: number(parts[0])?,
+            standard_block_chance: number(parts[1])?,
+            blocks_per_generation: number(parts[2])? as usize,
+        };
+        if !(1..=5).contains(&config.blocks_per_generation) {
+            return Err(format!("每次生成方块数应在1-5之间: {}", text));
+        }
+        Ok(config)
+    }
+
+    /// 写成 parse 能读取的格式
+    pub fn to_text(&self) -> String {
+        format!("{},{},{}", self.simple_block_chance, self.standard_block_chance, self.blocks_per_generation)
+    }
+}
+
+/// 一次放置的结果
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlaceOutcome {
+    pub cleared_rows: [bool; 8],  // 被消除的行
+    pub cleared_cols: [bool; 8],  // 被消除的列
+    pub lines_cleared: u32,       // 消除的行列总数
+    pub points: u32,              // 本次得分
+    pub combo: u32,               // 放置后的连击数
+}
+
+pub struct GameEngine {
+    pub grid: Grid,
+    pub current_blocks: Vec<BlockShape>,  // 当前可选方块
+    pub score: u32,
+    pub combo: u32,
+    pub moves: u32,                        // 本局已放置的方块数
+    pub config: GameConfig,
+    rng: SimpleRandom,
+}
+
+impl GameEngine {
+    pub fn new(config: GameConfig, seed: u64) -> Self {
+        let mut engine = GameEngine {
+            grid: Grid::new(),
+            current_blocks: Vec::new(),
+            score: 0,
+            combo: 0,
+            moves: 0,
+            config,
+            rng: SimpleRandom::new(seed),
+        };
+        engine.generate_blocks();
+        engine
+    }
+
+    /// 使用新种子重新开始一局，保留生成配置
+    pub fn reset(&mut self, seed: u64) {
+        *self = GameEngine::new(self.config, seed);
+    }
+
+    /// 按配置生成一组新的候选方块
+    pub fn generate_blocks(&mut self) {
+        self.current_blocks.clear();
+        for _ in 0..self.config.blocks_per_generation {
+            let block = self.random_block();
+            self.current_blocks.push(block);
+        }
+    }
+
+    /// 按当前概率生成一个方块
+    pub fn random_block(&mut self) -> BlockShape {
+        BlockShape::random_with_chances_rng(
+            &mut self.rng,
+            self.config.simple_block_chance,
+            self.config.standard_block_chance,
+        )
+    }
+
+    /// 检查第block_idx个候选方块能否放在指定位置
+    pub fn can_place(&self, block_idx: usize, grid_x: i32, grid_y: i32) -> bool {
+        self.current_blocks
+            .get(block_idx)
+            .is_some_and(|block| self.grid.can_place_block(block, grid_x, grid_y))
+    }
+
+    /// 放置方块并结算消除、计分和连击，用完候选方块后自动补充
+    /// 索引无效或位置不可放置时返回None，状态不变
+    pub fn place(&mut self, block_idx: usize, grid_x: i32, grid_y: i32) -> Option<PlaceOutcome> {
+        if !self.can_place(block_idx, grid_x, grid_y) {
+            return None;
+        }
+
+        let block = self.current_blocks.remove(block_idx);
+        self.grid.place_block(&block, grid_x, grid_y);
+        self.moves += 1;
+
+        // 先记录哪些行和列是满的，与 check_and_clear 消除的行和列相同
+        let (cleared_rows, cleared_cols) = self.grid.full_lines();
+        let (rows_cleared, cols_cleared) = self.grid.check_and_clear();
+        let lines_cleared = rows_cleared + cols_cleared;
+
+        let points = if lines_cleared > 0 {
+            self.combo += 1;
+            lines_cleared * 100 * self.combo
+        } else {
+            self.combo = 0;
+            0
+        };
+        self.score += points;
+
+        // 如果没有方块了，生成新的
+        if self.current_blocks.is_empty() {
+            self.generate_blocks();
+        }
+
+        Some(PlaceOutcome {
+            cleared_rows,
+            cleared_cols,
+            lines_cleared,
+            points,
+            combo: self.combo,
+        })
+    }
+
+    /// 所有候选方块都无处可放时游戏结束
+    pub fn is_game_over(&self) -> bool {
+        !self.current_blocks.iter().any(|block| {
+            (0..8).any(|y| (0..8).any(|x| self.grid.can_place_block(block, x, y)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reported_lines_match_grid() {
+        let mut engine = GameEngine::new(GameConfig::default(), 1);
+        engine.grid = Grid::new();
+        for i in 0..8 {
+            if i != 2 {
+                engine.grid.cells[6][i] = Some(1);
+            }
+            if i != 6 {
+                engine.grid.cells[i][2] = Some(1);
+            }
+        }
+        engine.grid.cells[0][7] = Some(3);
+        engine.current_blocks = vec![
+            BlockShape::from_name("single", 0).unwrap(),
+            BlockShape::from_name("O", 0).unwrap(),
+        ];
+
+        let outcome = engine.place(0, 2, 6).unwrap();
+        assert_eq!(outcome.lines_cleared, 2);
+        assert_eq!(outcome.points, 200);
+        assert_eq!(outcome.combo, 1);
+        for i in 0..8 {
+            assert_eq!(outcome.cleared_rows[i], i == 6);
+            assert_eq!(outcome.cleared_cols[i], i == 2);
+            assert!(engine.grid.cells[6][i].is_none() && engine.grid.cells[i][2].is_none());
+        }
+        assert_eq!(engine.grid.cells[0][7], Some(3));
+        assert_eq!(engine.grid.full_lines(), ([false; 8], [false; 8]));
+        assert_eq!((engine.score, engine.moves, engine.current_blocks.len()), (200, 1, 1));
+
+        // 下一步没有消除，连击中断
+        let outcome = engine.place(0, 4, 0).unwrap();
+        assert_eq!((outcome.lines_cleared, outcome.points, outcome.combo), (0, 0, 0));
+        assert_eq!(engine.current_blocks.len(), engine.config.blocks_per_generation);
+    }
+
+    #[test]
+    fn invalid_placement_changes_nothing() {
+        let mut engine = GameEngine::new(GameConfig::default(), 5);
+        let blocks: Vec<&str> = engine.current_blocks.iter().map(|b| b.shape_name()).collect();
+        assert!(engine.place(0, 8, 8).is_none());
+        assert!(engine.place(engine.current_blocks.len(), 0, 0).is_none());
+        assert_eq!(engine.current_blocks.iter().map(|b| b.shape_name()).collect::<Vec<_>>(), blocks);
+        assert_eq!((engine.score, engine.moves), (0, 0));
+    }
+
+    #[test]
+    fn same_seed_generates_same_blocks() {
+        let names = |seed| {
+            let mut engine = GameEngine::new(GameConfig::default(), seed);
+            (0..30).map(|_| engine.random_block()).map(|b| (b.shape_name(), b.color)).collect::<Vec<_>>()
+        };
+        assert_eq!(names(3), names(3));
+        assert_ne!(names(3), names(4));
+    }
+}
@@ -0,0 +1,213 @@
+// 强化学习环境接口：gym风格的 reset(seed) / step(action)
+// 动作编码为 槽位 * 64 + y * 8 + x，槽位为候选方块索引
+use crate::engine::{GameConfig, GameEngine};
+
+/// 候选方块槽位的最大数量（每次最多生成5个方块）
+pub const MAX_TRAY: usize = 5;
+/// 方块形状占用矩阵的边长，所有形状都能放进5x5
+pub const SHAPE_SIZE: usize = 5;
+/// 动作空间大小
+pub const ACTION_COUNT: usize = MAX_TRAY * 64;
+
+/// 把动作编号拆成 (槽位, x, y)
+pub fn decode_action(action: usize) -> (usize, i32, i32) {
+    let slot = action / 64;
+    let cell = action % 64;
+    (slot, (cell % 8) as i32, (cell / 8) as i32)
+}
+
+/// 把 (槽位, x, y) 编成动作编号
+pub fn encode_action(slot: usize, grid_x: i32, grid_y: i32) -> usize {
+    slot * 64 + grid_y as usize * 8 + grid_x as usize
+}
+
+/// 环境观测
+#[derive(Clone, PartialEq, Debug)]
+pub struct Observation {
+    pub grid: [[u8; 8]; 8],                                 // 网格占用，1为有方块
+    pub tray: [[[u8; SHAPE_SIZE]; SHAPE_SIZE]; MAX_TRAY],   // 每个槽位的形状占用矩阵，空槽位全为0
+    pub tray_len: usize,                                    // 当前候选方块数量
+    pub action_mask: Vec<bool>,                             // 长度为ACTION_COUNT的合法动作掩码
+    pub score: u32,
+    pub combo: u32,
+}
+
+/// 单步的附加信息
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StepInfo {
+    pub lines_cleared: u32,
+    pub points: u32,
+    pub combo: u32,
+    pub score: u32,
+    pub moves: u32,
+    pub invalid_action: bool,  // 动作不合法时为true，状态不变
+}
+
+/// step的返回值
+#[derive(Clone, Debug)]
+pub struct StepResult {
+    pub observation: Observation,
+    pub reward: f32,
+    pub done: bool,
+    pub info: StepInfo,
+}
+
+pub struct Env {
+    engine: GameEngine,
+}
+
+impl Env {
+    pub fn new(config: GameConfig) -> Self {
+        Env {
+            engine: GameEngine::new(config, 0),
+        }
+    }
+
+    /// 当前生成配置，下一次reset生效
+    pub fn config_mut(&mut self) -> &mut GameConfig {
+        &mut self.engine.config
+    }
+
+    /// 底层规则引擎（只读）
+    pub fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    /// 用指定种子开始新的一局
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.engine.reset(seed);
+        self.observation()
+    }
+
+    /// 执行一个动作，奖励为本步得分
+    /// 不合法的动作不改变状态，奖励为0并在info中标记
+    pub fn step(&mut self, action: usize) -> StepResult {
+        let (slot, grid_x, grid_y) = decode_action(action);
+        let outcome = if action < ACTION_COUNT {
+            self.engine.place(slot, grid_x, grid_y)
+        } else {
+            None
+        };
+
+        let info = StepInfo {
+            lines_cleared: outcome.map_or(0, |o| o.lines_cleared),
+            points: outcome.map_or(0, |o| o.points),
+            combo: self.engine.combo,
+            score: self.engine.score,
+            moves: self.engine.moves,
+            invalid_action: outcome.is_none(),
+        };
+
+        StepResult {
+            observation: self.observation(),
+            reward: info.points as f32,
+            done: self.engine.is_game_over(),
+            info,
+        }
+    }
+
+    /// 当前观测
+    pub fn observation(&self) -> Observation {
+        let engine = &self.engine;
+
+        let mut grid = [[0u8; 8]; 8];
+        for (y, row) in engine.grid.cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                grid[y][x] = cell.is_some() as u8;
+            }
+        }
+
+        let mut tray = [[[0u8; SHAPE_SIZE]; SHAPE_SIZE]; MAX_TRAY];
+        for (slot, block) in engine.current_blocks.iter().take(MAX_TRAY).enumerate() {
+            for &(dx, dy) in &block.cells {
+                if (0..SHAPE_SIZE as i32).contains(&dx) && (0..SHAPE_SIZE as i32).contains(&dy) {
+                    tray[slot][dy as usize][dx as usize] = 1;
+                }
+            }
+        }
+
+        let action_mask = (0..ACTION_COUNT)
+            .map(|action| {
+                let (slot, grid_x, grid_y) = decode_action(action);
+                engine.can_place(slot, grid_x, grid_y)
+            })
+            .collect();
+
+        Observation {
+            grid,
+            tray,
+            tray_len: engine.current_blocks.len().min(MAX_TRAY),
+            action_mask,
+            score: engine.score,
+            combo: engine.combo,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::legal_moves;
+
+    #[test]
+    fn same_seed_gives_same_observations() {
+        let mut a = Env::new(GameConfig::default());
+        let mut b = Env::new(GameConfig::default());
+        assert_eq!(a.reset(42), b.reset(42));
+        // 每步都选第一个合法动作，两个环境应完全同步
+        for _ in 0..20 {
+            let observation = a.observation();
+            let Some(action) = observation.action_mask.iter().position(|&legal| legal) else { break };
+            let (ra, rb) = (a.step(action), b.step(action));
+            assert_eq!(ra.observation, rb.observation);
+            assert_eq!(ra.reward, rb.reward);
+            if ra.done {
+                break;
+            }
+        }
+        assert_eq!(a.reset(7), b.reset(7));
+    }
+
+    #[test]
+    fn masked_action_is_rejected_without_changing_state() {
+        let mut env = Env::new(GameConfig::default());
+        let before = env.reset(3);
+        let masked = before.action_mask.iter().position(|&legal| !legal).unwrap();
+        for action in [masked, ACTION_COUNT, usize::MAX] {
+            let result = env.step(action);
+            assert!(result.info.invalid_action);
+            assert_eq!(result.reward, 0.0);
+            assert_eq!(result.info.moves, 0);
+            assert_eq!(result.observation, before);
+        }
+    }
+
+    #[test]
+    fn action_mask_matches_legal_moves() {
+        let mut env = Env::new(GameConfig::default());
+        env.reset(11);
+        for _ in 0..10 {
+            let observation = env.observation();
+            let engine = env.engine();
+            let mut expected = vec![false; ACTION_COUNT];
+            for m in legal_moves(&engine.grid, &engine.current_blocks) {
+                expected[encode_action(m.block_idx, m.grid_x, m.grid_y)] = true;
+            }
+            assert_eq!(observation.action_mask, expected);
+
+            let Some(action) = expected.iter().position(|&legal| legal) else { break };
+            if env.step(action).done {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn action_encoding_round_trips() {
+        for action in 0..ACTION_COUNT {
+            let (slot, x, y) = decode_action(action);
+            assert!(slot < MAX_TRAY && (0..8).contains(&x) && (0..8).contains(&y));
+            assert_eq!(encode_action(slot, x, y), action);
+        }
+    }
+}
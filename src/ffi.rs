@@ -0,0 +1,321 @@
+// ffi.rs - 对外导出的C ABI接口
+// 网页（WASM）和原生宿主可以通过这些函数驱动规则引擎，自行实现界面
+// 所有函数只使用整数参数和返回值，不需要在宿主和引擎之间传递内存
+// 对应的C头文件见 include/block_blast.h
+use crate::block::PALETTE_SIZE;
+use crate::engine::{GameConfig, GameEngine};
+use crate::env::MAX_TRAY;
+
+// 与 include/block_blast.h 中的同名宏一致
+/// 网格边长
+pub const BB_GRID_SIZE: i32 = 8;
+/// 方块颜色（调色板索引）的数量
+pub const BB_PALETTE_SIZE: i32 = PALETTE_SIZE as i32;
+/// 每次最多生成的候选方块数量
+pub const BB_MAX_TRAY: i32 = MAX_TRAY as i32;
+/// bb_game_cell：空格子
+pub const BB_CELL_EMPTY: i32 = -1;
+/// bb_game_cell：坐标越界或空指针
+pub const BB_CELL_INVALID: i32 = -2;
+/// bb_game_place：无法放置
+pub const BB_PLACE_INVALID: i32 = -1;
+
+/// 使用默认配置创建一局游戏，返回的指针需用 bb_game_free 释放
+#[no_mangle]
+pub extern "C" fn bb_game_new(seed: u32) -> *mut GameEngine {
+    Box::into_raw(Box::new(GameEngine::new(GameConfig::default(), seed as u64)))
+}
+
+/// 使用自定义方块生成配置创建一局游戏
+/// blocks_per_generation 会被限制在1-5之间
+#[no_mangle]
+pub extern "C" fn bb_game_new_with_config(
+    seed: u32,
+    simple_chance: i32,
+    standard_chance: i32,
+    blocks_per_generation: i32,
+) -> *mut GameEngine {
+    let config = GameConfig {
+        simple_block_chance: simple_chance,
+        standard_block_chance: standard_chance,
+        blocks_per_generation: blocks_per_generation.clamp(1, BB_MAX_TRAY) as usize,
+    };
+    Box::into_raw(Box::new(GameEngine::new(config, seed as u64)))
+}
+
+/// 释放游戏
+///
+/// # Safety
+/// game 必须是 bb_game_new 返回且尚未释放的指针，或为空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_free(game: *mut GameEngine) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// 用新种子重新开始，保留生成配置
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_reset(game: *mut GameEngine, seed: u32) {
+    if let Some(game) = game.as_mut() {
+        game.reset(seed as u64);
+    }
+}
+
+/// 网格单元格：-1为空，否则为调色板索引（0-6，颜色由主题决定），坐标越界返回-2
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_cell(game: *const GameEngine, x: i32, y: i32) -> i32 {
+    let Some(game) = game.as_ref() else { return BB_CELL_INVALID };
+    if !(0..BB_GRID_SIZE).contains(&x) || !(0..BB_GRID_SIZE).contains(&y) {
+        return BB_CELL_INVALID;
+    }
+    match game.grid.cells[y as usize][x as usize] {
+        Some(color) => color as i32,
+        None => BB_CELL_EMPTY,
+    }
+}
+
+/// 当前候选方块数量
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_tray_len(game: *const GameEngine) -> i32 {
+    game.as_ref().map_or(0, |game| game.current_blocks.len() as i32)
+}
+
+/// 第slot个候选方块的单元格数量，槽位无效返回0
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_tray_cell_count(game: *const GameEngine, slot: i32) -> i32 {
+    tray_block(game, slot).map_or(0, |block| block.cells.len() as i32)
+}
+
+/// 第slot个候选方块第i个单元格的x偏移，无效时返回-1
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_tray_cell_x(game: *const GameEngine, slot: i32, i: i32) -> i32 {
+    tray_block(game, slot)
+        .and_then(|block| block.cells.get(usize::try_from(i).ok()?))
+        .map_or(-1, |&(dx, _)| dx)
+}
+
+/// 第slot个候选方块第i个单元格的y偏移，无效时返回-1
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_tray_cell_y(game: *const GameEngine, slot: i32, i: i32) -> i32 {
+    tray_block(game, slot)
+        .and_then(|block| block.cells.get(usize::try_from(i).ok()?))
+        .map_or(-1, |&(_, dy)| dy)
+}
+
+/// 第slot个候选方块的调色板索引，槽位无效返回-1
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_tray_color(game: *const GameEngine, slot: i32) -> i32 {
+    tray_block(game, slot).map_or(-1, |block| block.color as i32)
+}
+
+/// 检查能否把第slot个候选方块放在(x, y)，可以返回1，否则返回0
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_can_place(game: *const GameEngine, slot: i32, x: i32, y: i32) -> i32 {
+    let Some(game) = game.as_ref() else { return 0 };
+    let Ok(slot) = usize::try_from(slot) else { return 0 };
+    game.can_place(slot, x, y) as i32
+}
+
+/// 放置第slot个候选方块，返回消除的行列数；不可放置时返回-1且状态不变
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_place(game: *mut GameEngine, slot: i32, x: i32, y: i32) -> i32 {
+    let Some(game) = game.as_mut() else { return BB_PLACE_INVALID };
+    let Ok(slot) = usize::try_from(slot) else { return BB_PLACE_INVALID };
+    game.place(slot, x, y).map_or(BB_PLACE_INVALID, |outcome| outcome.lines_cleared as i32)
+}
+
+/// 当前分数
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_score(game: *const GameEngine) -> u32 {
+    game.as_ref().map_or(0, |game| game.score)
+}
+
+/// 当前连击数
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_combo(game: *const GameEngine) -> u32 {
+    game.as_ref().map_or(0, |game| game.combo)
+}
+
+/// 游戏是否结束，结束返回1，否则返回0（空指针视为结束）
+///
+/// # Safety
+/// game 必须是有效的游戏指针或空指针
+#[no_mangle]
+pub unsafe extern "C" fn bb_game_is_over(game: *const GameEngine) -> i32 {
+    game.as_ref().is_none_or(|game| game.is_game_over()) as i32
+}
+
+// 按槽位取候选方块
+unsafe fn tray_block<'a>(game: *const GameEngine, slot: i32) -> Option<&'a crate::block::BlockShape> {
+    let game = game.as_ref()?;
+    game.current_blocks.get(usize::try_from(slot).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    const HEADER: &str = include_str!("../include/block_blast.h");
+
+    #[test]
+    fn play_through_the_c_api() {
+        unsafe {
+            let game = bb_game_new_with_config(7, 100, 0, 9);
+            assert!(!game.is_null());
+            assert_eq!(bb_game_tray_len(game), BB_MAX_TRAY);
+            assert_eq!((bb_game_score(game), bb_game_combo(game), bb_game_is_over(game)), (0, 0, 0));
+            for y in 0..BB_GRID_SIZE {
+                for x in 0..BB_GRID_SIZE {
+                    assert_eq!(bb_game_cell(game, x, y), BB_CELL_EMPTY);
+                }
+            }
+
+            // 第一个候选方块放在左上角，网格上对应的格子变为方块的颜色
+            let color = bb_game_tray_color(game, 0);
+            assert!((0..BB_PALETTE_SIZE).contains(&color));
+            let count = bb_game_tray_cell_count(game, 0);
+            let cells: Vec<(i32, i32)> = (0..count)
+                .map(|i| (bb_game_tray_cell_x(game, 0, i), bb_game_tray_cell_y(game, 0, i)))
+                .collect();
+            assert_eq!(bb_game_can_place(game, 0, 0, 0), 1);
+            assert_eq!(bb_game_place(game, 0, 0, 0), 0);
+            for &(x, y) in &cells {
+                assert_eq!(bb_game_cell(game, x, y), color);
+            }
+            assert_eq!(bb_game_tray_len(game), BB_MAX_TRAY - 1);
+            // 同一位置已被占用
+            assert_eq!(bb_game_can_place(game, 0, 0, 0), 0);
+            assert_eq!(bb_game_place(game, 0, 0, 0), BB_PLACE_INVALID);
+
+            bb_game_reset(game, 7);
+            assert_eq!(bb_game_cell(game, 0, 0), BB_CELL_EMPTY);
+            assert_eq!(bb_game_tray_len(game), BB_MAX_TRAY);
+            bb_game_free(game);
+        }
+    }
+
+    #[test]
+    fn clearing_a_line_scores() {
+        unsafe {
+            let game = bb_game_new(1);
+            let engine = &mut *game;
+            for x in 1..8 {
+                engine.grid.cells[0][x] = Some(0);
+            }
+            engine.current_blocks = vec![crate::block::BlockShape::from_name("single", 2).unwrap()];
+            assert_eq!(bb_game_place(game, 0, 0, 0), 1);
+            assert_eq!((bb_game_score(game), bb_game_combo(game)), (100, 1));
+            assert_eq!(bb_game_cell(game, 3, 0), BB_CELL_EMPTY);
+            bb_game_free(game);
+        }
+    }
+
+    #[test]
+    fn invalid_arguments_return_error_codes() {
+        unsafe {
+            let null = ptr::null_mut();
+            assert_eq!(bb_game_cell(null, 0, 0), BB_CELL_INVALID);
+            assert_eq!(bb_game_tray_len(null), 0);
+            assert_eq!(bb_game_tray_cell_count(null, 0), 0);
+            assert_eq!(bb_game_tray_cell_x(null, 0, 0), -1);
+            assert_eq!(bb_game_tray_color(null, 0), -1);
+            assert_eq!(bb_game_can_place(null, 0, 0, 0), 0);
+            assert_eq!(bb_game_place(null, 0, 0, 0), BB_PLACE_INVALID);
+            assert_eq!((bb_game_score(null), bb_game_combo(null), bb_game_is_over(null)), (0, 0, 1));
+            bb_game_reset(null, 1);
+            bb_game_free(null);
+
+            let game = bb_game_new(3);
+            let len = bb_game_tray_len(game);
+            for (x, y) in [(-1, 0), (0, -1), (BB_GRID_SIZE, 0), (0, BB_GRID_SIZE)] {
+                assert_eq!(bb_game_cell(game, x, y), BB_CELL_INVALID);
+            }
+            for slot in [-1, len, i32::MAX] {
+                assert_eq!(bb_game_tray_cell_count(game, slot), 0);
+                assert_eq!(bb_game_tray_cell_x(game, slot, 0), -1);
+                assert_eq!(bb_game_tray_cell_y(game, slot, 0), -1);
+                assert_eq!(bb_game_tray_color(game, slot), -1);
+                assert_eq!(bb_game_can_place(game, slot, 0, 0), 0);
+                assert_eq!(bb_game_place(game, slot, 0, 0), BB_PLACE_INVALID);
+            }
+            assert_eq!(bb_game_tray_cell_x(game, 0, -1), -1);
+            assert_eq!(bb_game_tray_cell_y(game, 0, bb_game_tray_cell_count(game, 0)), -1);
+            assert_eq!(bb_game_place(game, 0, BB_GRID_SIZE, 0), BB_PLACE_INVALID);
+            assert_eq!(bb_game_tray_len(game), len);
+            assert_eq!(bb_game_score(game), 0);
+            bb_game_free(game);
+
+            let game = bb_game_new_with_config(1, 30, 60, 0);
+            assert_eq!(bb_game_tray_len(game), 1);
+            bb_game_free(game);
+        }
+    }
+
+    #[test]
+    fn header_matches_rust() {
+        let defines: Vec<(&str, i32)> = HEADER
+            .lines()
+            .filter_map(|line| line.strip_prefix("#define BB_"))
+            .filter_map(|rest| rest.split_once(' '))
+            .map(|(name, value)| (name, value.trim().trim_matches(|c| c == '(' || c == ')').parse().unwrap()))
+            .collect();
+        assert_eq!(defines, vec![
+            ("GRID_SIZE", BB_GRID_SIZE),
+            ("PALETTE_SIZE", BB_PALETTE_SIZE),
+            ("MAX_TRAY", BB_MAX_TRAY),
+            ("CELL_EMPTY", BB_CELL_EMPTY),
+            ("CELL_INVALID", BB_CELL_INVALID),
+            ("PLACE_INVALID", BB_PLACE_INVALID),
+        ]);
+
+        // 头文件声明的函数与本文件导出的函数一一对应
+        let declared = |text: &str, marker: &str| {
+            let mut names: Vec<String> = text
+                .lines()
+                .filter(|line| line.contains(marker) && !line.trim_start().starts_with("/*"))
+                .filter_map(|line| line.split("bb_game_").nth(1))
+                .map(|rest| rest.split('(').next().unwrap().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        let exported = declared(include_str!("ffi.rs"), "extern \"C\" fn bb_game_");
+        assert_eq!(exported.len(), 15);
+        assert_eq!(declared(HEADER, "bb_game_"), exported);
+    }
+}
@@ -0,0 +1,302 @@
+// TrueType字体子集化：只保留需要的字符的字形，用来减小内嵌中文字体的体积
+// 保留原来的字形编号，只清空不需要的字形数据，因此 hmtx、maxp 等表可以原样保留
+// 重写的表：glyf/loca（只含用到的字形和复合字形引用的部件）、cmap（只含保留的字符）
+// 丢弃的表：GPOS等排版表（macroquad不使用）
+// 全部是纯函数，由 src/bin/subset_font.rs 调用生成子集字体，运行时用 char_set 判断字符是否在子集中
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+// 子集字体中保留的表，其余的表丢弃
+const KEPT_TABLES: [&[u8; 4]; 10] = [b"OS/2", b"cmap", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"name", b"post"];
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// 读取表目录：标签 -> 表数据
+fn tables(data: &[u8]) -> Option<BTreeMap<[u8; 4], &[u8]>> {
+    let count = u16_at(data, 4)? as usize;
+    let mut tables = BTreeMap::new();
+    for i in 0..count {
+        let record = 12 + i * 16;
+        let tag: [u8; 4] = data.get(record..record + 4)?.try_into().ok()?;
+        let offset = u32_at(data, record + 8)? as usize;
+        let length = u32_at(data, record + 12)? as usize;
+        tables.insert(tag, data.get(offset..offset + length)?);
+    }
+    Some(tables)
+}
+
+// 解析cmap中的Unicode子表（格式4或格式12），返回 字符 -> 字形编号
+fn parse_cmap(cmap: &[u8]) -> Option<BTreeMap<char, u16>> {
+    let count = u16_at(cmap, 2)? as usize;
+    let mut best: Option<usize> = None;
+    for i in 0..count {
+        let record = 4 + i * 8;
+        let platform = u16_at(cmap, record)?;
+        let encoding = u16_at(cmap, record + 2)?;
+        let offset = u32_at(cmap, record + 4)? as usize;
+        let format = u16_at(cmap, offset)?;
+        let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+        // 优先使用能表示BMP以外字符的格式12
+        if unicode && (format == 12 || (format == 4 && best.is_none())) {
+            best = Some(offset);
+        }
+    }
+    let table = &cmap[best?..];
+    let mut map = BTreeMap::new();
+    match u16_at(table, 0)? {
+        4 => {
+            let seg_count = u16_at(table, 6)? as usize / 2;
+            let end_codes = 14;
+            let start_codes = end_codes + seg_count * 2 + 2;
+            let deltas = start_codes + seg_count * 2;
+            let range_offsets = deltas + seg_count * 2;
+            for seg in 0..seg_count {
+                let end = u16_at(table, end_codes + seg * 2)?;
+                let start = u16_at(table, start_codes + seg * 2)?;
+                let delta = u16_at(table, deltas + seg * 2)?;
+                let range_offset = u16_at(table, range_offsets + seg * 2)? as usize;
+                for code in start..=end {
+                    if code == 0xFFFF {
+                        break;
+                    }
+                    let glyph = if range_offset == 0 {
+                        code.wrapping_add(delta)
+                    } else {
+                        let at = range_offsets + seg * 2 + range_offset + (code - start) as usize * 2;
+                        match u16_at(table, at)? {
+                            0 => 0,
+                            glyph => glyph.wrapping_add(delta),
+                        }
+                    };
+                    if let (Some(c), true) = (char::from_u32(code as u32), glyph != 0) {
+                        map.insert(c, glyph);
+                    }
+                }
+            }
+        }
+        12 => {
+            let groups = u32_at(table, 12)? as usize;
+            for group in 0..groups {
+                let record = 16 + group * 12;
+                let start = u32_at(table, record)?;
+                let end = u32_at(table, record + 4)?;
+                let first_glyph = u32_at(table, record + 8)?;
+                for code in start..=end {
+                    let glyph = first_glyph + (code - start);
+                    if let (Some(c), Ok(glyph)) = (char::from_u32(code), u16::try_from(glyph)) {
+                        if glyph != 0 {
+                            map.insert(c, glyph);
+                        }
+                    }
+                }
+            }
+        }
+        _ => return None,
+    }
+    Some(map)
+}
+
+/// 字体包含字形的所有字符，无法解析时返回空集合
+pub fn char_set(font: &[u8]) -> HashSet<char> {
+    tables(font)
+        .and_then(|tables| parse_cmap(tables.get(b"cmap")?))
+        .map(|map| map.into_keys().collect())
+        .unwrap_or_default()
+}
+
+// 每个字形在glyf表中的数据范围
+fn glyph_ranges(head: &[u8], loca: &[u8], num_glyphs: usize) -> Option<Vec<(usize, usize)>> {
+    let long_offsets = u16_at(head, 50)? != 0;
+    let offset = |i: usize| -> Option<usize> {
+        if long_offsets {
+            u32_at(loca, i * 4).map(|v| v as usize)
+        } else {
+            u16_at(loca, i * 2).map(|v| v as usize * 2)
+        }
+    };
+    (0..num_glyphs).map(|i| Some((offset(i)?, offset(i + 1)?))).collect()
+}
+
+// 复合字形引用的部件字形编号
+fn components(glyph: &[u8]) -> Vec<u16> {
+    const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+    const WE_HAVE_A_SCALE: u16 = 0x0008;
+    const MORE_COMPONENTS: u16 = 0x0020;
+    const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+    const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+
+    let mut result = Vec::new();
+    // 轮廓数为负表示复合字形，部件从10字节的字形头之后开始
+    if glyph.len() < 10 || (u16_at(glyph, 0).unwrap_or(0) as i16) >= 0 {
+        return result;
+    }
+    let mut at = 10;
+    while let (Some(flags), Some(index)) = (u16_at(glyph, at), u16_at(glyph, at + 2)) {
+        result.push(index);
+        at += 4 + if flags & ARG_1_AND_2_ARE_WORDS != 0 { 4 } else { 2 };
+        at += if flags & WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+        if flags & MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+    result
+}
+
+// 只含给定字符的cmap表：一个格式4子表，同时登记为Unicode和Windows Unicode平台
+fn build_cmap(map: &BTreeMap<char, u16>) -> Vec<u8> {
+    // 字符和字形编号都连续的部分合并为一段
+    let mut segments: Vec<(u16, u16, u16)> = Vec::new(); // (起始字符, 结束字符, 起始字形)
+    for (&c, &glyph) in map.iter().filter(|(c, _)| (**c as u32) < 0xFFFF) {
+        let code = c as u32 as u16;
+        match segments.last_mut() {
+            Some((start, end, first)) if *end + 1 == code && first.wrapping_add(code - *start) == glyph => *end = code,
+            _ => segments.push((code, code, glyph)),
+        }
+    }
+    // 格式4要求以0xFFFF结尾的段
+    segments.push((0xFFFF, 0xFFFF, 0));
+
+    let seg_count = segments.len() as u16;
+    let search_range = 2 * (1u16 << (15 - seg_count.leading_zeros()));
+    let entry_selector = (search_range / 2).trailing_zeros() as u16;
+    let mut sub = Vec::new();
+    let length = 16 + segments.len() * 8;
+    for value in [4, length as u16, 0, seg_count * 2, search_range, entry_selector, seg_count * 2 - search_range] {
+        sub.extend_from_slice(&value.to_be_bytes());
+    }
+    for &(_, end, _) in &segments {
+        sub.extend_from_slice(&end.to_be_bytes());
+    }
+    sub.extend_from_slice(&0u16.to_be_bytes());
+    for &(start, _, _) in &segments {
+        sub.extend_from_slice(&start.to_be_bytes());
+    }
+    for &(start, _, first) in &segments {
+        let delta = if start == 0xFFFF { 1 } else { first.wrapping_sub(start) };
+        sub.extend_from_slice(&delta.to_be_bytes());
+    }
+    for _ in &segments {
+        sub.extend_from_slice(&0u16.to_be_bytes());
+    }
+
+    let mut cmap = Vec::new();
+    for value in [0u16, 2] {
+        cmap.extend_from_slice(&value.to_be_bytes());
+    }
+    for (platform, encoding) in [(0u16, 3u16), (3, 1)] {
+        cmap.extend_from_slice(&platform.to_be_bytes());
+        cmap.extend_from_slice(&encoding.to_be_bytes());
+        cmap.extend_from_slice(&20u32.to_be_bytes());
+    }
+    cmap.extend_from_slice(&sub);
+    cmap
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+// 按表目录格式组装字体文件，并写入head表的checksumAdjustment
+fn assemble(tables: &BTreeMap<[u8; 4], Vec<u8>>) -> Vec<u8> {
+    let count = tables.len() as u16;
+    let search_range = 16 * (1u16 << (15 - count.leading_zeros()));
+    let entry_selector = (search_range / 16).trailing_zeros() as u16;
+    let mut font = Vec::new();
+    font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    for value in [count, search_range, entry_selector, count * 16 - search_range] {
+        font.extend_from_slice(&value.to_be_bytes());
+    }
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(data).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().div_ceil(4) * 4;
+    }
+    for data in tables.values() {
+        font.extend_from_slice(data);
+        font.resize(font.len().div_ceil(4) * 4, 0);
+    }
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font));
+        font[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+/// 生成只包含chars中字符的子集字体；字体中没有的字符忽略
+/// 字体不是glyf轮廓的TrueType字体或数据损坏时返回None
+pub fn subset(font: &[u8], chars: &BTreeSet<char>) -> Option<Vec<u8>> {
+    let tables = tables(font)?;
+    let head = *tables.get(b"head")?;
+    let glyf = *tables.get(b"glyf")?;
+    let num_glyphs = u16_at(tables.get(b"maxp")?, 4)? as usize;
+    let ranges = glyph_ranges(head, tables.get(b"loca")?, num_glyphs)?;
+    let cmap: BTreeMap<char, u16> = parse_cmap(tables.get(b"cmap")?)?
+        .into_iter()
+        .filter(|(c, _)| chars.contains(c))
+        .collect();
+
+    // 需要的字形：.notdef、字符对应的字形，以及复合字形递归引用的部件
+    let mut keep: BTreeSet<u16> = cmap.values().copied().collect();
+    keep.insert(0);
+    let mut pending: Vec<u16> = keep.iter().copied().collect();
+    while let Some(glyph) = pending.pop() {
+        let (start, end) = *ranges.get(glyph as usize)?;
+        for part in components(glyf.get(start..end)?) {
+            if keep.insert(part) {
+                pending.push(part);
+            }
+        }
+    }
+
+    // 保留字形编号，不需要的字形长度为0；统一使用32位loca
+    let mut new_glyf = Vec::new();
+    let mut new_loca = Vec::with_capacity((num_glyphs + 1) * 4);
+    for (glyph, &(start, end)) in ranges.iter().enumerate() {
+        new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+        if keep.contains(&(glyph as u16)) {
+            new_glyf.extend_from_slice(glyf.get(start..end)?);
+            new_glyf.resize(new_glyf.len().div_ceil(4) * 4, 0);
+        }
+    }
+    new_loca.extend_from_slice(&(new_glyf.len() as u32).to_be_bytes());
+
+    let mut new_head = head.to_vec();
+    new_head.get_mut(50..52)?.copy_from_slice(&1u16.to_be_bytes());
+    new_head.get_mut(8..12)?.copy_from_slice(&[0; 4]);
+
+    let mut out: BTreeMap<[u8; 4], Vec<u8>> = tables
+        .iter()
+        .filter(|(tag, _)| KEPT_TABLES.contains(tag))
+        .map(|(tag, data)| (*tag, data.to_vec()))
+        .collect();
+    out.insert(*b"glyf", new_glyf);
+    out.insert(*b"loca", new_loca);
+    out.insert(*b"head", new_head);
+    out.insert(*b"cmap", build_cmap(&cmap));
+    Some(assemble(&out))
+}
@@ -0,0 +1,229 @@
+// macroquad绘制后端：按层执行命令列表
+// 连续的方块命令收集到一个网格中一次绘制（按当前皮肤使用贴图或立体边缘），其他命令立即绘制
+// 遇到非方块命令或混合方式改变时先画完已收集的方块，命令不按贴图或混合方式重新排序，保持命令列表的绘制顺序
+// 对局画面的方块层只有方块，网格、候选区、飞行和消除中的方块一次绘制，拖拽层的方块再绘制一次
+// 叠加混合的命令使用单独的着色器材质，着色器无法加载时退回普通混合
+use std::cell::OnceCell;
+
+use macroquad::miniquad::{BlendFactor, BlendState, BlendValue, Equation};
+use macroquad::prelude::*;
+
+use crate::render::{bevel_quads, block_glyph, BlendMode, DrawCommand, DrawList, RenderBackend};
+use crate::skin::{self, Skin, SkinStyle};
+use crate::text::draw_text_styled;
+use crate::theme::Bevel;
+
+// 一次绘制的最大索引数，超过时分成多次（macroquad单次绘制最多5000个索引）
+const MAX_BATCH_INDICES: usize = 4800;
+
+#[derive(Default)]
+pub struct GpuRenderer {
+    additive_material: OnceCell<Option<Material>>,
+}
+
+impl GpuRenderer {
+    pub fn new() -> Self {
+        GpuRenderer::default()
+    }
+
+    // 切换混合方式前先画完已收集的方块
+    fn set_blend(&self, blend: BlendMode, batch: &mut BlockBatch) {
+        batch.flush();
+        match blend {
+            BlendMode::Alpha => gl_use_default_material(),
+            BlendMode::Additive => {
+                if let Some(material) = self.additive_material.get_or_init(load_additive_material) {
+                    gl_use_material(material);
+                }
+            },
+        }
+    }
+}
+
+impl RenderBackend for GpuRenderer {
+    fn draw_list(&mut self, list: &DrawList) {
+        let skin = skin::current();
+        let mut batch = BlockBatch::new(&skin);
+        let mut blend = BlendMode::Alpha;
+        for item in list.sorted() {
+            if item.blend != blend {
+                blend = item.blend;
+                self.set_blend(blend, &mut batch);
+            }
+            match &item.command {
+                DrawCommand::Block { rect, index, color, bevel, glyph } => {
+                    batch.block(*rect, *index, *color, bevel, *glyph);
+                },
+                command => {
+                    batch.flush();
+                    draw_command(command);
+                },
+            }
+        }
+        if blend != BlendMode::Alpha {
+            self.set_blend(BlendMode::Alpha, &mut batch);
+        }
+        batch.flush();
+    }
+}
+
+/// 用macroquad立即绘制一条命令，方块总是画成立体边缘
+pub fn draw_command(command: &DrawCommand) {
+    match command {
+        DrawCommand::Rect { rect, color } => draw_rectangle(rect.x, rect.y, rect.w, rect.h, *color),
+        DrawCommand::RectLines { rect, thickness, color } => {
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, *thickness, *color)
+        },
+        DrawCommand::Line { from, to, thickness, color } => draw_line(from.x, from.y, to.x, to.y, *thickness, *color),
+        DrawCommand::Triangle { points, color } => draw_triangle(points[0], points[1], points[2], *color),
+        DrawCommand::Quad { points, color } => {
+            draw_triangle(points[0], points[1], points[2], *color);
+            draw_triangle(points[0], points[2], points[3], *color);
+        },
+        DrawCommand::Circle { center, radius, color } => draw_circle(center.x, center.y, *radius, *color),
+        DrawCommand::CircleLines { center, radius, thickness, color } => {
+            draw_circle_lines(center.x, center.y, *radius, *thickness, *color)
+        },
+        DrawCommand::Text { text, pos, style } => {
+            draw_text_styled(text, pos.x, pos.y, style);
+        },
+        DrawCommand::Block { rect, index, color, bevel, glyph } => {
+            for (quad, color) in bevel_quads(*rect, *color, bevel) {
+                draw_command(&DrawCommand::Quad { points: quad, color });
+            }
+            if *glyph {
+                for command in block_glyph(*rect, *color, *index) {
+                    draw_command(&command);
+                }
+            }
+        },
+    }
+}
+
+// 收集连续的方块，flush时合成一个网格一次绘制（超过索引上限时分成多次），再画色盲图案
+struct BlockBatch<'a> {
+    skin: &'a Skin,
+    mesh: Mesh,
+    glyphs: Vec<(Rect, Color, u8)>,  // 方块位置、颜色、调色板索引
+}
+
+impl<'a> BlockBatch<'a> {
+    fn new(skin: &'a Skin) -> Self {
+        let texture = match &skin.style {
+            SkinStyle::Bevel => None,
+            SkinStyle::Atlas { texture, .. } => Some(texture.clone()),
+        };
+        BlockBatch {
+            skin,
+            mesh: Mesh { vertices: Vec::new(), indices: Vec::new(), texture },
+            glyphs: Vec::new(),
+        }
+    }
+
+    fn block(&mut self, rect: Rect, index: u8, color: Color, bevel: &Bevel, glyph: bool) {
+        if self.mesh.indices.len() + 30 > MAX_BATCH_INDICES {
+            self.draw_mesh();
+        }
+        let skin = self.skin;
+        match &skin.style {
+            SkinStyle::Bevel => {
+                // 程序生成的立体方块：主体加上左上亮、右下暗的四条斜边
+                for (quad, color) in bevel_quads(rect, color, bevel) {
+                    self.push_quad(quad, [Vec2::ZERO; 4], color);
+                }
+            },
+            SkinStyle::Atlas { texture, sprite_size, tint } => {
+                let (uv, uv_size) = Skin::sprite_uv(texture, *sprite_size, if *tint { 0 } else { index as usize });
+                // 不着色的精灵保持原色，只保留淡出的透明度
+                let sprite_color = if *tint { color } else { Color::new(1.0, 1.0, 1.0, color.a) };
+                let corners = [
+                    rect.point(),
+                    Vec2::new(rect.right(), rect.y),
+                    Vec2::new(rect.right(), rect.bottom()),
+                    Vec2::new(rect.x, rect.bottom()),
+                ];
+                let uvs = [uv, uv + Vec2::new(uv_size.x, 0.0), uv + uv_size, uv + Vec2::new(0.0, uv_size.y)];
+                self.push_quad(corners, uvs, sprite_color);
+            },
+        }
+        if glyph {
+            self.glyphs.push((rect, color, index));
+        }
+    }
+
+    // 绘制收集到的方块和图案，然后清空
+    fn flush(&mut self) {
+        self.draw_mesh();
+        for (rect, color, index) in self.glyphs.drain(..) {
+            for command in block_glyph(rect, color, index) {
+                draw_command(&command);
+            }
+        }
+    }
+
+    fn draw_mesh(&mut self) {
+        if !self.mesh.indices.is_empty() {
+            draw_mesh(&self.mesh);
+        }
+        self.mesh.vertices.clear();
+        self.mesh.indices.clear();
+    }
+
+    fn push_quad(&mut self, corners: [Vec2; 4], uvs: [Vec2; 4], color: Color) {
+        let start = self.mesh.vertices.len() as u16;
+        for (corner, uv) in corners.iter().zip(uvs) {
+            self.mesh.vertices.push(Vertex::new(corner.x, corner.y, 0.0, uv.x, uv.y, color));
+        }
+        self.mesh.indices.extend_from_slice(&[start, start + 1, start + 2, start, start + 2, start + 3]);
+    }
+}
+
+// 与macroquad默认着色器相同，只把混合方式改为 源颜色×源透明度 + 目标颜色
+const ADDITIVE_VERTEX: &str = r#"#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}"#;
+
+const ADDITIVE_FRAGMENT: &str = r#"#version 100
+varying lowp vec4 color;
+varying lowp vec2 uv;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}"#;
+
+fn load_additive_material() -> Option<Material> {
+    let params = MaterialParams {
+        pipeline_params: PipelineParams {
+            color_blend: Some(BlendState::new(
+                Equation::Add,
+                BlendFactor::Value(BlendValue::SourceAlpha),
+                BlendFactor::One,
+            )),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let source = ShaderSource::Glsl { vertex: ADDITIVE_VERTEX, fragment: ADDITIVE_FRAGMENT };
+    match load_material(source, params) {
+        Ok(material) => Some(material),
+        Err(err) => {
+            println!("无法加载叠加混合着色器: {:?}", err);
+            None
+        }
+    }
+}
@@ -1,94 +1,18 @@
 // 网格模块，处理方块放置和消除逻辑
 use macroquad::prelude::*;
 use crate::block::BlockShape;
+use crate::render::{DrawList, Layer};
+use crate::theme::Theme;
 
-// 更高效的立体感方块绘制函数
-fn draw_cube_block(x: f32, y: f32, size: f32, color: Color) {
-    // 亮色和暗色偏移量
-    let light_factor = 0.4;
-    let dark_factor = 0.4;
-    
-    // 边缘厚度
-    let border = size * 0.15;
-    
-    // 创建亮色和暗色
-    let light_color = Color::new(
-        (color.r + light_factor).min(1.0),
-        (color.g + light_factor).min(1.0),
-        (color.b + light_factor).min(1.0),
-        color.a
-    );
-    
-    let dark_color = Color::new(
-        (color.r - dark_factor).max(0.0),
-        (color.g - dark_factor).max(0.0),
-        (color.b - dark_factor).max(0.0),
-        color.a
-    );
-    
-    // 1. 先绘制主体
-    draw_rectangle(x, y, size, size, color);
-    
-    // 2. 绘制四个边（只需要4次绘制调用）
-    // 上边 - 亮色
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x + size, y), 
-        Vec2::new(x + size - border, y + border),
-        light_color
-    );
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x + border, y + border), 
-        Vec2::new(x + size - border, y + border),
-        light_color
-    );
-    
-    // 左边 - 亮色
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x, y + size), 
-        Vec2::new(x + border, y + size - border),
-        light_color
-    );
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x + border, y + border), 
-        Vec2::new(x + border, y + size - border),
-        light_color
-    );
-    
-    // 右边 - 暗色
-    draw_triangle(
-        Vec2::new(x + size, y), 
-        Vec2::new(x + size, y + size), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-    draw_triangle(
-        Vec2::new(x + size, y), 
-        Vec2::new(x + size - border, y + border), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-    
-    // 下边 - 暗色
-    draw_triangle(
-        Vec2::new(x, y + size), 
-        Vec2::new(x + size, y + size), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-    draw_triangle(
-        Vec2::new(x, y + size), 
-        Vec2::new(x + border, y + size - border), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
+#[derive(Clone)]
+pub struct Grid {
+    pub cells: [[Option<u8>; 8]; 8],  // 每格方块的调色板索引
 }
 
-pub struct Grid {
-    pub cells: [[Option<Color>; 8]; 8],
+impl Default for Grid {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Grid {
@@ -105,7 +29,7 @@ impl Grid {
             let y = grid_y + dy;
             
             // 检查边界
-            if x < 0 || x >= 8 || y < 0 || y >= 8 {
+            if !(0..8).contains(&x) || !(0..8).contains(&y) {
                 return false;
             }
             
@@ -178,52 +102,99 @@ impl Grid {
         }
     }
     
-    // 检查并消除填满的行和列 (只有完全填满才消除)
+    // 填满的行和列 (只有完全填满才消除)
+    pub fn full_lines(&self) -> ([bool; 8], [bool; 8]) {
+        let full_rows = std::array::from_fn(|y| (0..8).all(|x| self.cells[y][x].is_some()));
+        let full_cols = std::array::from_fn(|x| (0..8).all(|y| self.cells[y][x].is_some()));
+        (full_rows, full_cols)
+    }
+    
+    // 消除填满的行和列，返回消除的行数和列数
+    // 先找出所有满的行和列再统一清除：同时填满的行和列都会被消除，交叉处的格子属于两条线
     pub fn check_and_clear(&mut self) -> (u32, u32) {
-        let mut rows_cleared = 0;
-        let mut cols_cleared = 0;
-        
-        // 检查行
-        for y in 0..8 {
-            if (0..8).all(|x| self.cells[y][x].is_some()) {
-                // 清除这一行
-                for x in 0..8 {
-                    self.cells[y][x] = None;
-                }
-                rows_cleared += 1;
-            }
-        }
+        let (full_rows, full_cols) = self.full_lines();
         
-        // 检查列
-        for x in 0..8 {
-            if (0..8).all(|y| self.cells[y][x].is_some()) {
-                // 清除这一列
-                for y in 0..8 {
-                    self.cells[y][x] = None;
+        for (y, row) in self.cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if full_rows[y] || full_cols[x] {
+                    *cell = None;
                 }
-                cols_cleared += 1;
             }
         }
         
+        let rows_cleared = full_rows.iter().filter(|&&full| full).count() as u32;
+        let cols_cleared = full_cols.iter().filter(|&&full| full).count() as u32;
         (rows_cleared, cols_cleared)
     }
     
-    // 绘制网格和方块
-    pub fn draw(&self, offset_x: f32, offset_y: f32, cell_size: f32) {
+    // 网格线画在棋盘层，已放置的方块画在方块层，origin为左上角
+    pub fn draw(&self, list: &mut DrawList, origin: Vec2, cell_size: f32, theme: &Theme) {
         for y in 0..8 {
             for x in 0..8 {
-                let pos_x = offset_x + x as f32 * cell_size;
-                let pos_y = offset_y + y as f32 * cell_size;
+                let cell = Rect::new(
+                    origin.x + x as f32 * cell_size,
+                    origin.y + y as f32 * cell_size,
+                    cell_size,
+                    cell_size
+                );
                 
-                // 绘制网格线 - 改为黑色
-                draw_rectangle_lines(pos_x, pos_y, cell_size, cell_size, 1.0, BLACK);
+                // 网格线
+                list.set_layer(Layer::Board);
+                list.rect_lines(cell, 1.0, theme.grid_lines);
                 
-                // 绘制已放置的方块
+                // 已放置的方块
                 if let Some(color) = self.cells[y][x] {
-                    // 使用draw_cube_block函数绘制方块（包含3D效果）
-                    draw_cube_block(pos_x, pos_y, cell_size, color);
+                    list.set_layer(Layer::Blocks);
+                    list.block(cell, color, 1.0, theme);
                 }
             }
         }
     }
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled_count(grid: &Grid) -> usize {
+        grid.cells.iter().flatten().filter(|cell| cell.is_some()).count()
+    }
+
+    #[test]
+    fn row_and_column_filled_together_both_clear() {
+        let mut grid = Grid::new();
+        for i in 0..8 {
+            if i != 5 {
+                grid.cells[3][i] = Some(1);
+            }
+            if i != 3 {
+                grid.cells[i][5] = Some(2);
+            }
+        }
+        grid.cells[0][0] = Some(4);
+        let single = BlockShape::from_name("single", 0).unwrap();
+        assert!(grid.can_place_block(&single, 5, 3));
+        grid.place_block(&single, 5, 3);
+        // 交叉的格子属于两条线，只占一格
+        assert_eq!(filled_count(&grid), 8 + 8 - 1 + 1);
+
+        let (rows, cols) = grid.full_lines();
+        assert_eq!(rows.iter().filter(|&&full| full).count(), 1);
+        assert!(rows[3] && cols[5]);
+        assert_eq!(grid.check_and_clear(), (1, 1));
+        assert_eq!(filled_count(&grid), 1);
+        assert_eq!(grid.cells[0][0], Some(4));
+        assert_eq!(grid.check_and_clear(), (0, 0));
+    }
+
+    #[test]
+    fn partial_lines_stay() {
+        let mut grid = Grid::new();
+        for x in 0..7 {
+            grid.cells[7][x] = Some(0);
+        }
+        assert_eq!(grid.full_lines(), ([false; 8], [false; 8]));
+        assert_eq!(grid.check_and_clear(), (0, 0));
+        assert_eq!(filled_count(&grid), 7);
+    }
+}
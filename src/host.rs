@@ -0,0 +1,96 @@
+// 与宿主网页通信的桥接层
+// Rust→JS：通过导入函数 bb_host_event(kind, value) 发送游戏事件（仅WASM）
+// JS→Rust：宿主调用导出函数 bb_host_command(cmd)，命令进入队列，由游戏循环每帧取出处理
+// 对应的JS插件见 block_blast_bridge.js
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+/// 发送给宿主页面的事件
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HostEvent {
+    GameStarted,
+    ScoreChanged(u32),
+    GameOver(u32),   // 最终得分
+    HighScore(u32),  // 本局创造的新纪录
+    Paused,
+    Resumed,
+}
+
+impl HostEvent {
+    // 事件编号和附带数值，编号与JS插件中的EVENT_NAMES一致
+    fn code(self) -> (i32, i32) {
+        match self {
+            HostEvent::GameStarted => (0, 0),
+            HostEvent::ScoreChanged(score) => (1, score as i32),
+            HostEvent::GameOver(score) => (2, score as i32),
+            HostEvent::HighScore(score) => (3, score as i32),
+            HostEvent::Paused => (4, 0),
+            HostEvent::Resumed => (5, 0),
+        }
+    }
+}
+
+/// 宿主页面发来的命令
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HostCommand {
+    Pause,
+    Resume,
+    Restart,
+    QuitToMenu,
+}
+
+impl HostCommand {
+    // 编号与JS插件中的COMMANDS一致
+    fn from_code(code: i32) -> Option<Self> {
+        match code {
+            0 => Some(HostCommand::Pause),
+            1 => Some(HostCommand::Resume),
+            2 => Some(HostCommand::Restart),
+            3 => Some(HostCommand::QuitToMenu),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    fn bb_host_event(kind: i32, value: i32);
+}
+
+/// 向宿主页面发送事件，原生平台上不做任何事
+pub fn emit(event: HostEvent) {
+    let (kind, value) = event.code();
+    #[cfg(target_arch = "wasm32")]
+    unsafe {
+        bb_host_event(kind, value);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = (kind, value);
+}
+
+// 等待游戏循环处理的命令
+static PENDING_COMMANDS: Lazy<Mutex<Vec<HostCommand>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// 宿主页面调用的命令入口，命令有效返回1，否则返回0
+#[no_mangle]
+pub extern "C" fn bb_host_command(cmd: i32) -> i32 {
+    match HostCommand::from_code(cmd) {
+        Some(command) => {
+            push_command(command);
+            1
+        }
+        None => 0,
+    }
+}
+
+/// 把命令加入队列
+pub fn push_command(command: HostCommand) {
+    if let Ok(mut pending) = PENDING_COMMANDS.lock() {
+        pending.push(command);
+    }
+}
+
+/// 取出所有待处理的命令
+pub fn poll_commands() -> Vec<HostCommand> {
+    PENDING_COMMANDS.lock().map(|mut pending| std::mem::take(&mut *pending)).unwrap_or_default()
+}
@@ -0,0 +1,309 @@
+// 对局界面布局：根据屏幕大小和方向计算网格、分数栏和候选区的位置，以及暂停按钮和菜单、设置界面按钮的位置
+// 绘制和点击判定都使用同一份布局，屏幕大小或每次生成的方块数量变化时重新计算
+// 竖屏时候选区在网格下方，横屏时在网格右侧
+use macroquad::prelude::*;
+
+/// 屏幕方向
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+/// 对局界面各区域的位置和尺寸（逻辑像素）
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    pub screen: Vec2,
+    pub orientation: Orientation,
+    pub header: Rect,          // 顶部标题栏，放标题和暂停按钮
+    pub grid: Rect,            // 8x8网格（正方形）
+    pub cell_size: f32,
+    pub score_y: f32,          // 分数和最高分文字的中线
+    pub separator: (Vec2, Vec2), // 网格和候选区之间的分隔线
+    pub tray: Rect,            // 候选区背景
+    pub tray_title_y: f32,     // 候选区标题的中线
+    pub tray_block_size: f32,  // 每个候选方块占用的区域边长
+    blocks_per_generation: usize,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::new(Vec2::new(400.0, 600.0), 3)
+    }
+}
+
+impl Layout {
+    /// 按屏幕大小和每次生成的方块数量计算布局
+    pub fn new(screen: Vec2, blocks_per_generation: usize) -> Self {
+        if screen.x > screen.y {
+            Self::landscape(screen, blocks_per_generation)
+        } else {
+            Self::portrait(screen, blocks_per_generation)
+        }
+    }
+
+    /// 屏幕大小或方块数量变化后需要重新计算
+    pub fn is_stale(&self, screen: Vec2, blocks_per_generation: usize) -> bool {
+        self.screen != screen || self.blocks_per_generation != blocks_per_generation
+    }
+
+    // 竖屏：标题、网格、分数、分隔线和候选区自上而下排列
+    fn portrait(screen: Vec2, blocks_per_generation: usize) -> Self {
+        let (width, height) = (screen.x, screen.y);
+        let header = Rect::new(0.0, 0.0, width, height * 0.07);
+        // 窗口变宽时由高度限制网格，给候选区留出空间
+        let grid_size = (width * 0.9).min(height * 0.6);
+        let cell_size = grid_size / 8.0;
+        let grid = Rect::new((width - grid_size) / 2.0, header.bottom(), grid_size, grid_size);
+
+        // 小屏幕上缩小间距
+        let is_small_screen = height < 600.0;
+        let spacing = if is_small_screen { 20.0 } else { 30.0 };
+        let separator_y = grid.bottom() + 15.0 + spacing;
+        let tray_top = separator_y + if is_small_screen { 2.0 } else { 5.0 };
+        // 确保候选区至少占屏幕高度的20%
+        let tray_height = (height - tray_top).max(height * 0.2);
+
+        // 方块尺寸按最大方块数量确定，放置方块后剩余方块的大小不会突然变化
+        // 矮屏幕上候选区较矮，方块区域不超出候选区
+        let tray_block_size = if blocks_per_generation <= 2 {
+            cell_size * 4.0
+        } else {
+            let width_factor = if is_small_screen { 0.80 } else { 0.85 };
+            (width * width_factor) / (blocks_per_generation as f32 * 1.2)
+        }
+        .min(tray_height);
+
+        Layout {
+            screen,
+            orientation: Orientation::Portrait,
+            header,
+            grid,
+            cell_size,
+            score_y: grid.bottom() + 23.0,
+            separator: (Vec2::new(10.0, separator_y), Vec2::new(width - 10.0, separator_y)),
+            tray: Rect::new(0.0, tray_top, width, tray_height),
+            tray_title_y: tray_top + if is_small_screen { 15.0 } else { 25.0 },
+            tray_block_size,
+            blocks_per_generation,
+        }
+    }
+
+    // 横屏：网格和分数在左侧，候选区在右侧纵向排列
+    fn landscape(screen: Vec2, blocks_per_generation: usize) -> Self {
+        let (width, height) = (screen.x, screen.y);
+        let header = Rect::new(0.0, 0.0, width, height * 0.07);
+        // 网格下方留出分数栏
+        let score_height = 40.0;
+        let margin = width * 0.04;
+        let grid_size = (height - header.h - score_height).min(width * 0.6).max(0.0);
+        let cell_size = grid_size / 8.0;
+        let grid = Rect::new(margin, header.bottom(), grid_size, grid_size);
+
+        let separator_x = grid.right() + margin;
+        let tray = Rect::new(separator_x + 5.0, header.bottom(), (width - separator_x - 5.0).max(0.0), height - header.h);
+        let title_height = 50.0;
+        let column_height = tray.h - title_height;
+        let tray_block_size = (cell_size * 4.0)
+            .min(tray.w * 0.8)
+            .min(column_height / (blocks_per_generation.max(1) as f32 * 1.2));
+
+        Layout {
+            screen,
+            orientation: Orientation::Landscape,
+            header,
+            grid,
+            cell_size,
+            score_y: grid.bottom() + 23.0,
+            separator: (Vec2::new(separator_x, header.bottom() + 10.0), Vec2::new(separator_x, height - 10.0)),
+            tray,
+            tray_title_y: tray.y + 25.0,
+            tray_block_size,
+            blocks_per_generation,
+        }
+    }
+
+    /// 候选区中第idx个方块（共count个）的中心位置和方块区域边长
+    pub fn tray_slot(&self, idx: usize, count: usize) -> (Vec2, f32) {
+        let size = self.tray_block_size;
+        // 方块之间的间距随方块大小缩放
+        let margin = size * 0.2;
+        let total = size * count as f32 + margin * (count as f32 - 1.0);
+        let offset = size / 2.0 + idx as f32 * (size + margin);
+        let center = match self.orientation {
+            Orientation::Portrait => {
+                let start_x = (self.screen.x - total) / 2.0;
+                Vec2::new(start_x + offset, self.tray.y + self.tray.h / 2.0)
+            },
+            Orientation::Landscape => {
+                // 标题下方的区域内垂直居中
+                let top = self.tray_title_y + 25.0;
+                let start_y = top + (self.tray.bottom() - top - total) / 2.0;
+                Vec2::new(self.tray.x + self.tray.w / 2.0, start_y + offset)
+            },
+        };
+        (center, size)
+    }
+
+    /// 标题栏右侧的暂停按钮
+    pub fn pause_button(&self) -> Rect {
+        let size = self.header.h * 0.7;
+        Rect::new(self.header.right() - size - 10.0, self.header.y + (self.header.h - size) / 2.0, size, size)
+    }
+
+    /// 菜单底部的设置按钮
+    pub fn menu_settings_button(&self) -> Rect {
+        Rect::new(self.screen.x / 2.0 - 60.0, self.screen.y - 60.0, 120.0, 36.0)
+    }
+
+    /// 暂停菜单的count个按钮，纵向排列在屏幕中部
+    pub fn pause_menu_buttons(&self, count: usize) -> Vec<Rect> {
+        let width = self.screen.x * 0.6;
+        let height = 48.0;
+        let gap = 14.0;
+        let x = (self.screen.x - width) / 2.0;
+        let top = self.screen.y * 0.35;
+        (0..count).map(|i| Rect::new(x, top + i as f32 * (height + gap), width, height)).collect()
+    }
+
+    /// 设置界面的count行：(整行, 减小按钮, 增大按钮)，所有行排在标题和返回按钮之间
+    pub fn settings_rows(&self, count: usize) -> Vec<(Rect, Rect, Rect)> {
+        let width = self.screen.x * 0.9;
+        let x = (self.screen.x - width) / 2.0;
+        let top = self.screen.y * 0.18;
+        let pitch = ((self.screen.y * 0.84 - top) / count.max(1) as f32).min(60.0);
+        let height = pitch - 8.0;
+        let button = height.min(36.0);
+        (0..count)
+            .map(|i| {
+                let row = Rect::new(x, top + i as f32 * pitch, width, height);
+                let button_y = row.y + (height - button) / 2.0;
+                let minus = Rect::new(row.x + row.w * 0.55, button_y, button, button);
+                let plus = Rect::new(row.x + row.w - button - 8.0, button_y, button, button);
+                (row, minus, plus)
+            })
+            .collect()
+    }
+
+    /// 设置界面底部的返回按钮
+    pub fn settings_back_button(&self) -> Rect {
+        let width = self.screen.x * 0.5;
+        Rect::new((self.screen.x - width) / 2.0, self.screen.y * 0.86, width, 44.0)
+    }
+
+    /// 是否可以从这个位置开始拖动候选方块（分隔线的候选区一侧）
+    pub fn in_tray(&self, pos: Vec2) -> bool {
+        match self.orientation {
+            Orientation::Portrait => pos.y > self.separator.0.y,
+            Orientation::Landscape => pos.x > self.separator.0.x,
+        }
+    }
+
+    /// 网格格子左上角的屏幕坐标
+    pub fn cell_origin(&self, x: i32, y: i32) -> Vec2 {
+        Vec2::new(self.grid.x + x as f32 * self.cell_size, self.grid.y + y as f32 * self.cell_size)
+    }
+
+    /// 网格格子中心的屏幕坐标
+    pub fn cell_center(&self, x: i32, y: i32) -> Vec2 {
+        self.cell_origin(x, y) + Vec2::splat(self.cell_size / 2.0)
+    }
+
+    /// 屏幕坐标所在的网格格子（可能在网格之外）
+    pub fn cell_at(&self, pos: Vec2) -> (i32, i32) {
+        (
+            ((pos.x - self.grid.x) / self.cell_size).floor() as i32,
+            ((pos.y - self.grid.y) / self.cell_size).floor() as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREENS: [(f32, f32); 8] = [
+        (320.0, 480.0),
+        (400.0, 600.0),
+        (390.0, 844.0),
+        (768.0, 1024.0),
+        (600.0, 400.0),
+        (800.0, 600.0),
+        (1280.0, 720.0),
+        (1920.0, 1080.0),
+    ];
+
+    fn inside(outer: Rect, inner: Rect) -> bool {
+        const EPSILON: f32 = 0.01;
+        inner.x >= outer.x - EPSILON
+            && inner.y >= outer.y - EPSILON
+            && inner.right() <= outer.right() + EPSILON
+            && inner.bottom() <= outer.bottom() + EPSILON
+    }
+
+    // 只接触边缘不算重叠
+    fn overlaps(a: Rect, b: Rect) -> bool {
+        a.x < b.right() && b.x < a.right() && a.y < b.bottom() && b.y < a.bottom()
+    }
+
+    #[test]
+    fn regions_fit_on_screen_without_overlapping() {
+        for (w, h) in SCREENS {
+            for blocks in 1..=5 {
+                let layout = Layout::new(Vec2::new(w, h), blocks);
+                let screen = Rect::new(0.0, 0.0, w, h);
+                let expected = if w > h { Orientation::Landscape } else { Orientation::Portrait };
+                let name = format!("{}x{} {}", w, h, blocks);
+                assert_eq!(layout.orientation, expected, "{}", name);
+
+                for rect in [layout.header, layout.grid, layout.tray, layout.pause_button()] {
+                    assert!(inside(screen, rect), "{}: {:?}", name, rect);
+                }
+                assert!((layout.grid.w - layout.cell_size * 8.0).abs() < 0.01 && layout.grid.w == layout.grid.h);
+                assert!(!overlaps(layout.header, layout.grid), "{}", name);
+                assert!(!overlaps(layout.grid, layout.tray), "{}", name);
+                assert!(!overlaps(layout.header, layout.tray), "{}", name);
+                assert!(inside(layout.header, layout.pause_button()), "{}", name);
+                assert!(layout.score_y > layout.grid.bottom() && layout.score_y < h, "{}", name);
+
+                // 候选方块都在候选区内，且互不重叠
+                let slots: Vec<Rect> = (0..blocks)
+                    .map(|idx| {
+                        let (center, size) = layout.tray_slot(idx, blocks);
+                        Rect::new(center.x - size / 2.0, center.y - size / 2.0, size, size)
+                    })
+                    .collect();
+                for (i, slot) in slots.iter().enumerate() {
+                    assert!(inside(layout.tray, *slot), "{}: {:?}", name, slot);
+                    assert!(layout.in_tray(slot.center()), "{}", name);
+                    assert!(slots[i + 1..].iter().all(|other| !overlaps(*slot, *other)), "{}", name);
+                }
+                assert!(!layout.in_tray(layout.grid.center()), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn menu_buttons_fit_on_screen() {
+        for (w, h) in SCREENS {
+            let layout = Layout::new(Vec2::new(w, h), 3);
+            let screen = Rect::new(0.0, 0.0, w, h);
+            assert!(inside(screen, layout.menu_settings_button()));
+
+            let pause = layout.pause_menu_buttons(4);
+            for (i, rect) in pause.iter().enumerate() {
+                assert!(inside(screen, *rect));
+                assert!(pause[i + 1..].iter().all(|other| !overlaps(*rect, *other)));
+            }
+
+            let back = layout.settings_back_button();
+            assert!(inside(screen, back));
+            let rows = layout.settings_rows(13);
+            for (i, (row, minus, plus)) in rows.iter().enumerate() {
+                assert!(inside(screen, *row) && inside(*row, *minus) && inside(*row, *plus));
+                assert!(!overlaps(*minus, *plus) && !overlaps(*row, back));
+                assert!(rows[i + 1..].iter().all(|(other, ..)| !overlaps(*row, *other)));
+            }
+        }
+    }
+}
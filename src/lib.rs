@@ -6,6 +6,31 @@ pub mod block;
 pub mod grid; 
 pub mod save; 
 pub mod effects;
+pub mod particles;
+pub mod audio;
+pub mod synth;
+pub mod music;
+pub mod settings;
+pub mod tween;
+pub mod display;
+pub mod text;
+pub mod theme;
+pub mod skin;
+pub mod render;
+pub mod gpu;
+pub mod scene;
+pub mod font_subset;
+pub mod locale;
+pub mod random;
+pub mod bot;
+pub mod engine;
+pub mod layout;
+pub mod simulation;
+pub mod replay;
+pub mod snapshot;
+pub mod raster;
+pub mod env;
+pub mod ffi;
  
 // 注意：此库仅使用macroquad处理WASM导出 
 // 此版本中不使用wasm-bindgen 
@@ -0,0 +1,154 @@
+// 本地化：界面文本按消息ID从各语言的字符串表中查找
+// 字符串表位于 resources/locales/<语言代码>.txt，编译时嵌入，格式见 zh-CN.txt 的文件头
+// 当前语言的字体无法加载时（例如缺少日文字体），文本退回英文，英文可以用默认字体显示；这样的语言也不出现在设置中
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+use crate::text;
+
+/// 支持的语言
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Locale {
+    ZhCn,
+    En,
+    Ja,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::ZhCn, Locale::En, Locale::Ja];
+
+    /// 语言代码，也是字符串表的文件名
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::En => "en",
+            Locale::Ja => "ja",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Locale> {
+        Locale::ALL.into_iter().find(|locale| locale.code() == code)
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Locale::ZhCn => include_str!("../resources/locales/zh-CN.txt"),
+            Locale::En => include_str!("../resources/locales/en.txt"),
+            Locale::Ja => include_str!("../resources/locales/ja.txt"),
+        }
+    }
+
+    fn index(self) -> usize {
+        Locale::ALL.iter().position(|&locale| locale == self).unwrap_or(0)
+    }
+
+    /// 这个语言使用的字体名称，没有指定时使用默认字体
+    pub fn font(self) -> Option<&'static str> {
+        TABLES[self.index()].get("_font").map(String::as_str)
+    }
+
+    /// 字体已加载（或不需要专门的字体），可以在设置中选择
+    pub fn is_available(self) -> bool {
+        self.font().is_none_or(text::has_font)
+    }
+
+    // 数量对应的复数形式（CLDR分类），中文和日文不区分单复数
+    fn plural_category(self, count: i64) -> &'static str {
+        match self {
+            Locale::En if count == 1 => "one",
+            _ => "other",
+        }
+    }
+}
+
+// 解析后的字符串表，按 Locale::ALL 的顺序排列
+static TABLES: Lazy<Vec<HashMap<String, String>>> =
+    Lazy::new(|| Locale::ALL.iter().map(|locale| parse_table(locale.source())).collect());
+
+static CURRENT: Lazy<Mutex<Locale>> = Lazy::new(|| Mutex::new(Locale::ZhCn));
+
+// 逐行的 ID = 文本，忽略空行和 # 开头的注释
+fn parse_table(source: &str) -> HashMap<String, String> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(id, value)| (id.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// 切换语言，新语言的字体由 text::load_pending_fonts 在主循环中加载
+pub fn set_locale(locale: Locale) {
+    *CURRENT.lock().unwrap() = locale;
+}
+
+pub fn current_locale() -> Locale {
+    *CURRENT.lock().unwrap()
+}
+
+/// 实际用来显示文本的语言：当前语言的字体不可用时退回英文
+pub fn display_locale() -> Locale {
+    let locale = current_locale();
+    match locale.font() {
+        Some(font) if locale != Locale::En && !text::has_font(font) => Locale::En,
+        _ => locale,
+    }
+}
+
+// 依次在显示语言、英文和源语言（中文）中查找，都没有时返回ID本身
+fn lookup(locale: Locale, id: &str) -> Option<&'static str> {
+    [locale, Locale::En, Locale::ZhCn]
+        .iter()
+        .find_map(|l| TABLES[l.index()].get(id))
+        .map(String::as_str)
+}
+
+// 把文本中的 {name} 替换为参数，未知的参数保持原样
+fn format(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        match args.iter().find(|(name, _)| *name == &after[..end]) {
+            Some((_, value)) => out.push_str(&value.to_string()),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 查找消息ID对应的文本
+pub fn tr(id: &str) -> String {
+    tr_args(id, &[])
+}
+
+/// 查找消息ID对应的文本并填入 {name} 参数
+pub fn tr_args(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    match lookup(display_locale(), id) {
+        Some(template) => format(template, args),
+        None => id.to_string(),
+    }
+}
+
+/// 按count选择复数形式（ID.one / ID.other），count本身作为 {count} 参数
+pub fn tr_count(id: &str, count: i64, args: &[(&str, &dyn Display)]) -> String {
+    let locale = display_locale();
+    let plural_id = format!("{}.{}", id, locale.plural_category(count));
+    let template = lookup(locale, &plural_id).or_else(|| lookup(locale, &format!("{}.other", id)));
+    let mut all_args: Vec<(&str, &dyn Display)> = vec![("count", &count)];
+    all_args.extend_from_slice(args);
+    match template {
+        Some(template) => format(template, &all_args),
+        None => id.to_string(),
+    }
+}
@@ -1,6 +1,4 @@
 use macroquad::prelude::*;
-use std::sync::Mutex;
-use once_cell::sync::Lazy;
 
 // 如果需要，显式导入TextAlign
 // use macroquad::text::TextAlign;
@@ -9,6 +7,28 @@ pub mod block;
 pub mod grid;
 pub mod save;
 pub mod effects;
+pub mod particles;
+pub mod audio;
+pub mod synth;
+pub mod music;
+pub mod settings;
+pub mod tween;
+pub mod display;
+pub mod text;
+pub mod theme;
+pub mod skin;
+pub mod render;
+pub mod gpu;
+pub mod scene;
+pub mod font_subset;
+pub mod locale;
+pub mod random;
+pub mod bot;
+pub mod engine;
+pub mod env;
+pub mod layout;
+pub mod host;
+pub mod ffi;
 
 // 移除不必要的导入
 // use wasm_bindgen::prelude::*;
@@ -19,42 +39,9 @@ pub mod effects;
 // use crate::save;
 // use crate::effects;
 
-// 将字体数据直接嵌入到可执行文件中
-const CHINESE_FONT_DATA: &[u8] = include_bytes!("../resources/fonts/SourceHanSansCN-Medium.ttf");
-
-// 全局中文字体变量 - 使用Font类型
-static CHINESE_FONT: Lazy<Mutex<Option<Font>>> = Lazy::new(|| Mutex::new(None));
-
-// 获取设备DPI缩放比例的辅助函数
-fn get_dpi_scale() -> f32 {
-    #[cfg(target_os = "ios")]
-    {
-        // iOS设备通常有更高的像素密度，使用更高的缩放比
-        // iPhone的Retina显示屏通常是2x或3x缩放
-        return 3.0;
-    }
-    
-    #[cfg(target_os = "android")]
-    {
-        // Android设备根据屏幕密度调整
-        // 通常为1.5x到4x之间
-        return 2.0;
-    }
-    
-    // 桌面平台，根据实际DPI动态计算
-    // macroquad没有直接提供获取系统DPI的API，所以我们使用推断
-    let (w, h) = (screen_width(), screen_height());
-    if w > 2000.0 || h > 2000.0 {
-        // 4K或高分辨率显示器
-        2.0
-    } else if w > 1200.0 || h > 1200.0 {
-        // 高清显示器
-        1.5
-    } else {
-        // 标准显示器
-        1.0
-    }
-}
+use locale::{tr, tr_args, tr_count};
+use render::{DrawList, Layer, RenderBackend};
+use text::{text_rect, HAlign, TextStyle, VAlign};
 
 // WASM初始化代码
 #[cfg(target_arch = "wasm32")]
@@ -68,290 +55,454 @@ pub extern "C" fn wasm_init() {
     }));
 }
 
-// 更高效的立体感方块绘制函数
-pub fn draw_cube_block(x: f32, y: f32, size: f32, color: Color) {
-    // 获取DPI缩放因子
-    let dpi_scale = get_dpi_scale();
-    
-    // 亮色和暗色偏移量
-    let light_factor = 0.4;
-    let dark_factor = 0.4;
-    
-    // 边缘厚度 - 在高DPI屏幕上相应调整
-    let border = size * 0.15;
-    
-    // 创建亮色和暗色
-    let light_color = Color::new(
-        (color.r + light_factor).min(1.0),
-        (color.g + light_factor).min(1.0),
-        (color.b + light_factor).min(1.0),
-        color.a
-    );
-    
-    let dark_color = Color::new(
-        (color.r - dark_factor).max(0.0),
-        (color.g - dark_factor).max(0.0),
-        (color.b - dark_factor).max(0.0),
-        color.a
-    );
-    
-    // 在高DPI设备上使用抗锯齿绘制，提高视觉质量
-    // 1. 先绘制主体
-    draw_rectangle(x, y, size, size, color);
-    
-    // 2. 绘制四个边（只需要4次绘制调用）
-    // 上边 - 亮色
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x + size, y), 
-        Vec2::new(x + size - border, y + border),
-        light_color
-    );
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x + border, y + border), 
-        Vec2::new(x + size - border, y + border),
-        light_color
-    );
-    
-    // 左边 - 亮色
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x, y + size), 
-        Vec2::new(x + border, y + size - border),
-        light_color
-    );
-    draw_triangle(
-        Vec2::new(x, y), 
-        Vec2::new(x + border, y + border), 
-        Vec2::new(x + border, y + size - border),
-        light_color
-    );
-    
-    // 右边 - 暗色
-    draw_triangle(
-        Vec2::new(x + size, y), 
-        Vec2::new(x + size, y + size), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-    draw_triangle(
-        Vec2::new(x + size, y), 
-        Vec2::new(x + size - border, y + border), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-    
-    // 下边 - 暗色
-    draw_triangle(
-        Vec2::new(x, y + size), 
-        Vec2::new(x + size, y + size), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-    draw_triangle(
-        Vec2::new(x, y + size), 
-        Vec2::new(x + border, y + size - border), 
-        Vec2::new(x + size - border, y + size - border),
-        dark_color
-    );
-}
-
-// 绘制中文文本的辅助函数(支持真正的中文渲染)
-fn draw_chinese_text(text: &str, x: f32, y: f32, font_size: f32, color: Color) {
-    // 安全获取字体锁，处理可能的错误
-    let chinese_font_result = CHINESE_FONT.lock();
-    
-    // 如果无法获取锁或字体未加载，回退到默认字体
-    if chinese_font_result.is_err() || chinese_font_result.as_ref().unwrap().is_none() {
-        // 回退到默认英文显示
-        let english_text = match text {
-            "逆向俄罗斯方块" => "Block Blast",
-            "点击开始游戏" => "Click to Start",
-            "最高分" => "High Score",
-            "简单模式" => "Easy Mode",
-            "普通模式" => "Normal Mode",
-            "按空格键切换游戏难度" => "Press Space to Change Difficulty",
-            "游戏结束" => "Game Over",
-            "最终得分" => "Final Score",
-            "点击重新开始" => "Click to Restart",
-            "分数" => "Score",
-            "连击" => "Combo",
-            "将方块拖放到网格中，填满行或列即可消除" => "Drag blocks to fill rows or columns",
-            _ if text.contains("简单方块概率") => "Block chances",
-            _ if text.contains("可拖拽方块数量") => "Draggable blocks",
-            _ if text.contains("消除") => "Cleared",
-            _ => text,
-        };
-        
-        // 使用传入的字体大小（已在外部应用了DPI缩放）
-        draw_text(
-            english_text, 
-            x - measure_text(english_text, None, font_size as u16, 1.0).width / 2.0, 
-            y, 
-            font_size, 
-            color
-        );
-        return;
-    }
-    
-    // 安全地获取字体引用
-    let chinese_font = chinese_font_result.unwrap();
-    let font = if let Some(font) = &*chinese_font {
-        font
-    } else {
-        // 这个分支不应该被执行，因为我们已经在前面检查了字体是否为None
-        // 但为了代码安全，我们仍然提供一个回退方案
-        let english_text = match text {
-            "逆向俄罗斯方块" => "Block Blast",
-            _ => text,
-        };
-        
-        draw_text(
-            english_text, 
-            x - measure_text(english_text, None, font_size as u16, 1.0).width / 2.0, 
-            y, 
-            font_size, 
-            color
-        );
-        return;
-    };
-    
-    // 使用macroquad的measure_text函数精确测量文本宽度
-    let text_dims = measure_text(
-        text,
-        Some(font),
-        font_size as u16,
-        1.0  // 字间距
-    );
-    
-    // 确保文本居中显示
-    let text_x = x - text_dims.width / 2.0;
-    
-    // 基线调整 - 解决垂直对齐问题
-    let baseline_adjustment = font_size * 0.2; // 基准值
-    
-    // 根据文本内容进行微调
-    let y_offset = match text {
-        "逆向俄罗斯方块" => -font_size * 0.1,  // 标题上移
-        "点击开始游戏" | "点击重新开始" => font_size * 0.05,  // 轻微下移
-        _ if text.contains("分数") || text.contains("最高分") => font_size * 0.05,
-        _ if text.contains("连击") => font_size * 0.05,
-        _ if text.chars().count() >= 10 => font_size * 0.08,  // 长文本稍微下移
-        _ => 0.0  // 默认不偏移
-    };
-    
-    // 绘制文本
-    draw_text_ex(
-        text,
-        text_x,
-        y + baseline_adjustment + y_offset,
-        TextParams {
-            font_size: font_size as u16,
-            font: Some(font),
-            color,
-            ..Default::default()
-        },
-    );
-}
-
 // 游戏状态枚举
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 enum GameState {
     Menu,
     Playing,
+    Paused,    // 暂停菜单，显示在对局画面之上
+    Settings,  // 设置界面，可从菜单或暂停菜单进入
     GameOver,
 }
 
+// 暂停菜单的按钮
+#[derive(Clone, Copy, PartialEq)]
+enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    QuitToMenu,
+}
+
+impl PauseAction {
+    const ALL: [PauseAction; 4] = [
+        PauseAction::Resume,
+        PauseAction::Restart,
+        PauseAction::Settings,
+        PauseAction::QuitToMenu,
+    ];
+
+    fn label(self) -> String {
+        tr(match self {
+            PauseAction::Resume => "pause.resume",
+            PauseAction::Restart => "pause.restart",
+            PauseAction::Settings => "pause.settings",
+            PauseAction::QuitToMenu => "pause.quit",
+        })
+    }
+}
+
+// 设置界面中的选项，每项通过左右按钮调整，音量还可以拖动滑条
+#[derive(Clone, Copy, PartialEq)]
+enum SettingItem {
+    Difficulty,
+    SimpleChance,
+    BlocksPerGeneration,
+    MusicVolume,
+    SfxVolume,
+    MusicMuted,
+    SfxMuted,
+    ReduceMotion,
+    Language,
+    UiScale,
+    Theme,
+    Colorblind,
+    Skin,
+}
+
+impl SettingItem {
+    const ALL: [SettingItem; 13] = [
+        SettingItem::Difficulty,
+        SettingItem::SimpleChance,
+        SettingItem::BlocksPerGeneration,
+        SettingItem::MusicVolume,
+        SettingItem::SfxVolume,
+        SettingItem::MusicMuted,
+        SettingItem::SfxMuted,
+        SettingItem::ReduceMotion,
+        SettingItem::Language,
+        SettingItem::UiScale,
+        SettingItem::Theme,
+        SettingItem::Colorblind,
+        SettingItem::Skin,
+    ];
+
+    fn label(self) -> String {
+        tr(match self {
+            SettingItem::Difficulty => "settings.difficulty",
+            SettingItem::SimpleChance => "settings.simple_chance",
+            SettingItem::BlocksPerGeneration => "settings.blocks_per_turn",
+            SettingItem::MusicVolume => "settings.music_volume",
+            SettingItem::SfxVolume => "settings.sfx_volume",
+            SettingItem::MusicMuted => "settings.music_muted",
+            SettingItem::SfxMuted => "settings.sfx_muted",
+            SettingItem::ReduceMotion => "settings.reduce_motion",
+            SettingItem::Language => "settings.language",
+            SettingItem::UiScale => "settings.ui_scale",
+            SettingItem::Theme => "settings.theme",
+            SettingItem::Colorblind => "settings.colorblind",
+            SettingItem::Skin => "settings.skin",
+        })
+    }
+    
+    // 是否以滑条显示
+    fn is_slider(self) -> bool {
+        matches!(self, SettingItem::MusicVolume | SettingItem::SfxVolume)
+    }
+}
+
 // 游戏数据
 struct Game {
     state: GameState,
-    grid: grid::Grid,
-    current_blocks: Vec<block::BlockShape>,  // 当前可选方块
-    score: u32,
-    combo: u32,
+    engine: engine::GameEngine,       // 网格、候选方块、分数和连击
+    seed: u64,                        // 当前对局的种子，决定方块序列和自动游玩的选择
     drag_block_idx: Option<usize>,    // 当前拖拽的方块索引
     drag_pos: Option<Vec2>,           // 拖拽位置
     drag_offset: Vec2,                // 新增：拖动偏移量，记录手指与方块的初始偏移
     save_data: save::SaveData,
     easy_mode: bool,                  // 简单模式标志
-    simple_block_chance: i32,         // 简单方块生成概率 (0-100)
-    standard_block_chance: i32,       // 标准方块生成概率 (0-100)
-    blocks_per_generation: usize,     // 每次生成的方块数量 (1-5)
     effects: effects::Effects,         // 特效系统
+    autoplay: Option<AutoPlay>,       // 自动游玩（演示或测试时启用）
+    attract_mode: bool,               // 是否为菜单空闲时的演示模式
+    menu_idle_time: f32,              // 菜单界面无操作的时间
+    settings_return: GameState,       // 关闭设置界面后返回的状态
+    slider_drag: Option<SettingItem>, // 正在拖动的音量滑条
+    music: music::Music,              // 背景音乐
+    drag_scale: tween::Tween<f32>,    // 拖拽中方块从候选区尺寸(0)放大到网格尺寸(1)的进度
+    flights: Vec<PieceFlight>,        // 松手后的方块动画
+    audio_started: bool,              // 玩家在菜单第一次点击后才开始播放音乐
+    stall_check_delay: u32,           // 还要跳过卡顿判断的帧数
+    high_score_at_start: u32,         // 本局开始时的最高分，用于判断新纪录
+    layout: layout::Layout,           // 对局界面布局，屏幕大小变化时重新计算
+}
+
+// 拖起方块时放大到网格尺寸、松手后滑回候选区、吸附到网格的动画时长（秒）
+const DRAG_SCALE_TIME: f32 = 0.15;
+const RETURN_TIME: f32 = 0.25;
+const SNAP_TIME: f32 = 0.1;
+
+// 松手后的方块动画：位置无效时滑回候选区，有效时吸附到网格
+// 位置用锚点表示：方块(0,0)格子的中心
+struct PieceFlight {
+    block: block::BlockShape,
+    slot: Option<usize>,           // 滑回候选区的槽位，终点每帧按槽位计算；吸附网格时为None
+    from: (Vec2, f32),             // 起点锚点和格子大小
+    to: (Vec2, f32),               // 吸附网格时的终点锚点和格子大小
+    progress: tween::Tween<f32>,
+}
+
+// 菜单空闲多久后进入演示模式（秒）
+const ATTRACT_IDLE_TIME: f32 = 10.0;
+// 机器人每步的思考停顿和拖拽动画时长（秒）
+const AUTOPLAY_THINK_TIME: f32 = 0.35;
+const AUTOPLAY_DRAG_TIME: f32 = 0.5;
+// 自动游玩时游戏结束后重新开始的等待时间（秒）
+const AUTOPLAY_RESTART_DELAY: f32 = 2.0;
+// 卡顿暂停：两帧间隔超过该值（秒）时自动暂停（仅原生平台）
+// 这不是焦点检测，只是卡顿判断：窗口最小化或程序被挂起时通常不再出新帧，
+// 但失去焦点仍在绘制的窗口不会暂停，超过该值的加载或卡顿也会暂停
+#[cfg(not(target_arch = "wasm32"))]
+const STALL_PAUSE_FRAME_GAP: f32 = 1.0;
+// 启动或读取文件后跳过卡顿判断的帧数，读取文件造成的长帧不算卡顿
+const STALL_CHECK_DELAY_FRAMES: u32 = 2;
+// 特效和计时器每帧最多推进的时间（秒），避免从后台恢复时跳变
+const MAX_FRAME_TIME: f32 = 0.1;
+
+// 自动游玩状态：机器人选好一步后，以拖拽动画把方块移动到目标位置
+struct AutoPlay {
+    bot: bot::Bot,
+    current: Option<bot::BotMove>,  // 正在执行的一步
+    from: Vec2,                      // 拖拽起点
+    timer: f32,                      // 当前阶段已经过的时间
+    games_played: u32,               // 已完成的局数，用于长时间测试日志
+}
+
+impl AutoPlay {
+    // 机器人的随机选择由对局种子派生，相同种子的自动对局可以复现
+    fn new(strength: bot::BotStrength, game_seed: u64) -> Self {
+        AutoPlay {
+            bot: bot::Bot::for_game(strength, game_seed),
+            current: None,
+            from: Vec2::ZERO,
+            timer: 0.0,
+            games_played: 0,
+        }
+    }
 }
 
 impl Game {
     fn new() -> Self {
+        let seed = (get_time() * 1000.0) as u64;
         Game {
             state: GameState::Menu,
-            grid: grid::Grid::new(),
-            current_blocks: Vec::new(),
-            score: 0,
-            combo: 0,
+            // 默认配置：简单方块30%、标准方块60%、每次生成3个方块
+            engine: engine::GameEngine::new(engine::GameConfig::default(), seed),
+            seed,
             drag_block_idx: None,
             drag_pos: None,
             drag_offset: Vec2::new(0.0, 0.0), // 初始化为零偏移
             save_data: save::SaveData::load(),
             easy_mode: true,          // 默认开启简单模式
-            simple_block_chance: 30,  // 简单方块30%概率
-            standard_block_chance: 60, // 标准方块60%概率
-            blocks_per_generation: 3, // 默认生成3个方块
             effects: effects::Effects::new(), // 初始化特效系统
+            autoplay: None,
+            attract_mode: false,
+            menu_idle_time: 0.0,
+            settings_return: GameState::Menu,
+            slider_drag: None,
+            music: music::Music::default(),
+            drag_scale: tween::Tween::fixed(1.0),
+            flights: Vec::new(),
+            audio_started: false,
+            stall_check_delay: STALL_CHECK_DELAY_FRAMES,
+            high_score_at_start: 0,
+            layout: layout::Layout::default(),
         }
     }
     
-    // 生成随机方块
-    fn generate_blocks(&mut self) {
-        self.current_blocks.clear();
-        
-        // 随机生成设定数量的方块
-        for _ in 0..self.blocks_per_generation {
-            let block = block::BlockShape::random_with_chances(self.simple_block_chance, self.standard_block_chance);
-            self.current_blocks.push(block);
+    // 开始新的一局
+    fn start_new_game(&mut self) {
+        self.state = GameState::Playing;
+        let seed = (get_time() * 1000.0) as u64;
+        self.seed = seed;
+        self.engine.reset(seed);
+        self.effects.reset(seed);
+        if let Some(auto) = self.autoplay.as_mut() {
+            auto.bot = bot::Bot::for_game(auto.bot.strength, seed);
+        }
+        self.flights.clear();
+        self.drag_block_idx = None;
+        self.drag_pos = None;
+        self.menu_idle_time = 0.0;
+        self.high_score_at_start = self.save_data.high_score;
+        // 演示和自动游玩的对局不通知宿主页面
+        if self.autoplay.is_none() {
+            host::emit(host::HostEvent::GameStarted);
+        }
+    }
+    
+    // 放弃当前对局（包括自动游玩）并重新开始
+    fn restart(&mut self) {
+        self.autoplay = None;
+        self.attract_mode = false;
+        self.start_new_game();
+    }
+    
+    // 暂停对局，演示模式不暂停
+    fn pause(&mut self) {
+        if self.state != GameState::Playing || self.attract_mode {
+            return;
+        }
+        self.state = GameState::Paused;
+        // 取消进行中的拖拽，方块回到候选区
+        self.drag_block_idx = None;
+        self.drag_pos = None;
+        if self.autoplay.is_none() {
+            host::emit(host::HostEvent::Paused);
+        }
+    }
+    
+    // 从暂停菜单（或由暂停菜单打开的设置界面）回到对局
+    fn resume(&mut self) {
+        let paused = self.state == GameState::Paused
+            || (self.state == GameState::Settings && self.settings_return == GameState::Paused);
+        if !paused {
+            return;
+        }
+        self.state = GameState::Playing;
+        if self.autoplay.is_none() {
+            host::emit(host::HostEvent::Resumed);
+        }
+    }
+    
+    // 打开设置界面，关闭后回到当前状态
+    fn open_settings(&mut self) {
+        self.settings_return = self.state;
+        self.state = GameState::Settings;
+    }
+    
+    // 当前是否有一局正在进行（包括暂停中）
+    fn in_game(&self) -> bool {
+        match self.state {
+            GameState::Playing | GameState::Paused => true,
+            GameState::Settings => self.settings_return == GameState::Paused,
+            _ => false,
         }
     }
     
+    // 设置项当前值的显示文本
+    fn setting_value(&self, item: SettingItem) -> String {
+        let config = &self.engine.config;
+        let settings = &self.save_data.settings;
+        let yes_no = |value: bool| tr(if value { "value.yes" } else { "value.no" });
+        match item {
+            SettingItem::Difficulty => tr(if self.easy_mode { "value.easy" } else { "value.normal" }),
+            SettingItem::SimpleChance => format!("{}%", config.simple_block_chance),
+            SettingItem::BlocksPerGeneration => tr_count("value.blocks", config.blocks_per_generation as i64, &[]),
+            SettingItem::MusicVolume => format!("{}%", (settings.music_volume * 100.0).round()),
+            SettingItem::SfxVolume => format!("{}%", (settings.sfx_volume * 100.0).round()),
+            SettingItem::MusicMuted => yes_no(settings.music_muted),
+            SettingItem::SfxMuted => yes_no(settings.sfx_muted),
+            SettingItem::ReduceMotion => yes_no(settings.reduce_motion),
+            SettingItem::Colorblind => yes_no(settings.colorblind),
+            SettingItem::Language => tr(&format!("language.{}", settings.language.code())),
+            SettingItem::UiScale => format!("{}%", (settings.ui_scale * 100.0).round()),
+            SettingItem::Theme => {
+                // 内置主题显示翻译后的名称，自定义主题显示文件中的名称
+                let theme = theme::current();
+                if theme.is_builtin() { tr(&format!("theme.{}", theme.name)) } else { theme.name.clone() }
+            },
+            SettingItem::Skin => {
+                let skin = skin::current();
+                if skin.is_builtin() { tr(&format!("skin.{}", skin.name)) } else { skin.name.clone() }
+            },
+        }
+    }
+    
+    // 滑条当前位置，0.0-1.0
+    fn slider_fraction(&self, item: SettingItem) -> f32 {
+        match item {
+            SettingItem::MusicVolume => self.save_data.settings.music_volume,
+            SettingItem::SfxVolume => self.save_data.settings.sfx_volume,
+            _ => 0.0,
+        }
+    }
+    
+    // 拖动滑条设置音量（不立即保存，松开时保存）
+    fn set_slider_fraction(&mut self, item: SettingItem, fraction: f32) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        match item {
+            SettingItem::MusicVolume => self.save_data.settings.music_volume = fraction,
+            SettingItem::SfxVolume => self.save_data.settings.sfx_volume = fraction,
+            _ => return,
+        }
+        self.apply_audio_settings();
+    }
+    
+    // 把音量设置应用到音效（背景音乐每帧读取设置）
+    fn apply_audio_settings(&mut self) {
+        self.effects.sounds.volume = self.save_data.settings.effective_sfx_volume();
+    }
+    
+    // 调整设置项，delta为-1或1
+    fn adjust_setting(&mut self, item: SettingItem, delta: i32) {
+        match item {
+            SettingItem::Difficulty => {
+                self.easy_mode = !self.easy_mode;
+            },
+            SettingItem::SimpleChance => {
+                // 简单方块概率每次调整10%，标准方块占剩余的80%
+                let config = &mut self.engine.config;
+                config.simple_block_chance = (config.simple_block_chance + delta * 10).clamp(0, 100);
+                config.standard_block_chance = ((100 - config.simple_block_chance) as f32 * 0.8) as i32;
+            },
+            SettingItem::BlocksPerGeneration => {
+                // 每次生成的方块数量 (1-5)，对局中立即增减候选方块
+                let count = (self.engine.config.blocks_per_generation as i32 + delta).clamp(1, 5) as usize;
+                self.engine.config.blocks_per_generation = count;
+                self.engine.current_blocks.truncate(count);
+                if self.in_game() && self.engine.current_blocks.len() < count {
+                    let block = self.engine.random_block();
+                    self.engine.current_blocks.push(block);
+                }
+            },
+            SettingItem::MusicVolume | SettingItem::SfxVolume => {
+                // 音量每次调整10%
+                let fraction = ((self.slider_fraction(item) * 10.0).round() + delta as f32) / 10.0;
+                self.set_slider_fraction(item, fraction);
+                self.save_data.save();
+            },
+            SettingItem::MusicMuted => {
+                self.save_data.settings.music_muted = !self.save_data.settings.music_muted;
+                self.save_data.save();
+            },
+            SettingItem::SfxMuted => {
+                self.save_data.settings.sfx_muted = !self.save_data.settings.sfx_muted;
+                self.apply_audio_settings();
+                self.save_data.save();
+            },
+            SettingItem::ReduceMotion => {
+                self.save_data.settings.reduce_motion = !self.save_data.settings.reduce_motion;
+                self.effects.reduce_motion = self.save_data.settings.reduce_motion;
+                self.save_data.save();
+            },
+            SettingItem::Language => {
+                // 在字体可用的语言中循环切换
+                let all: Vec<locale::Locale> = locale::Locale::ALL.into_iter().filter(|l| l.is_available()).collect();
+                let current = all.iter().position(|&l| l == self.save_data.settings.language).unwrap_or(0) as i32;
+                let next = all[(current + delta.signum()).rem_euclid(all.len() as i32) as usize];
+                self.save_data.settings.language = next;
+                locale::set_locale(next);
+                self.save_data.save();
+            },
+            SettingItem::UiScale => {
+                // 在可选的缩放之间调整，不循环
+                let scales = display::UI_SCALES;
+                let current = scales.iter()
+                    .position(|&s| (s - self.save_data.settings.ui_scale).abs() < 0.01)
+                    .unwrap_or(2) as i32;
+                let next = scales[(current + delta.signum()).clamp(0, scales.len() as i32 - 1) as usize];
+                self.save_data.settings.ui_scale = next;
+                display::set_user_scale(next);
+                self.save_data.save();
+            },
+            SettingItem::Colorblind => {
+                self.save_data.settings.colorblind = !self.save_data.settings.colorblind;
+                theme::set_colorblind(self.save_data.settings.colorblind);
+                self.save_data.save();
+            },
+            SettingItem::Theme => {
+                // 在内置主题和自定义主题中循环切换
+                let names = theme::names();
+                let current = names.iter().position(|n| *n == theme::current().name).unwrap_or(0) as i32;
+                let next = &names[(current + delta.signum()).rem_euclid(names.len() as i32) as usize];
+                theme::set_theme(next);
+                text::set_theme_font(theme::current().font.as_deref());
+                self.save_data.settings.theme = next.clone();
+                self.save_data.save();
+            },
+            SettingItem::Skin => {
+                // 在内置皮肤和皮肤包中循环切换
+                let names = skin::names();
+                let current = names.iter().position(|n| *n == skin::current().name).unwrap_or(0) as i32;
+                let next = &names[(current + delta.signum()).rem_euclid(names.len() as i32) as usize];
+                skin::set_skin(next);
+                self.save_data.settings.skin = next.clone();
+                self.save_data.save();
+            },
+        }
+    }
+    
+    // 处理宿主页面发来的命令
+    fn handle_host_command(&mut self, command: host::HostCommand) {
+        match command {
+            host::HostCommand::Pause => self.pause(),
+            host::HostCommand::Resume => self.resume(),
+            host::HostCommand::Restart => self.restart(),
+            host::HostCommand::QuitToMenu => self.return_to_menu(),
+        }
+    }
+    
+    // 屏幕大小或每次生成的方块数量变化时重新计算布局
+    fn refresh_layout(&mut self) {
+        let screen = Vec2::new(screen_width(), screen_height());
+        let blocks_per_generation = self.engine.config.blocks_per_generation;
+        if self.layout.is_stale(screen, blocks_per_generation) {
+            self.layout = layout::Layout::new(screen, blocks_per_generation);
+        }
+    }
+    
+    // 候选区中第idx个方块的中心位置和方块区域尺寸
+    fn tray_slot(&self, idx: usize) -> (Vec2, f32) {
+        self.layout.tray_slot(idx, self.engine.current_blocks.len())
+    }
+    
     // 处理拖拽开始
     fn start_drag(&mut self, mouse_pos: Vec2) {
-        // 检查是否点击了某个可选方块 - 竖屏模式下的布局
-        let grid_size = screen_width() * 0.9;
-        let cell_size = grid_size / 8.0;
-        
-        // 计算可拖拽方块区域的位置
-        // 使用动态计算的顶部偏移
-        let grid_offset_y = screen_height() * 0.07;
-        
-        // 检测小屏幕
-        let is_small_screen = screen_height() < 600.0;
-        let spacing = if is_small_screen { 20.0 } else { 30.0 };
-        let separator_y = grid_offset_y + grid_size + 15.0 + spacing;
-        let bottom_area_top = separator_y + (if is_small_screen { 2.0 } else { 5.0 });
-        
-        // 确保底部区域最小高度
-        let min_bottom_height = screen_height() * 0.2;
-        let bottom_area_height = (screen_height() - bottom_area_top).max(min_bottom_height);
-        let blocks_y = bottom_area_top + bottom_area_height / 2.0; // 垂直居中
-        
-        // 计算方块布局 - 根据最大方块数量(blocks_per_generation)确定尺寸
-        let max_block_size = cell_size * 4.0; // 最大方块尺寸
-        let block_size = if self.blocks_per_generation <= 2 {
-            max_block_size // 对于1-2个最大方块，使用最大尺寸
-        } else {
-            // 对于更多方块，减小尺寸以适应屏幕
-            // 考虑屏幕大小，在小屏幕上进一步减小尺寸
-            let width_factor = if is_small_screen { 0.80 } else { 0.85 };
-            (screen_width() * width_factor) / (self.blocks_per_generation as f32 * 1.2)
-        };
-        
-        let block_margin = block_size * 0.2; // 方块之间的间距根据方块大小缩放
-        let total_width = block_size * self.current_blocks.len() as f32 + block_margin * (self.current_blocks.len() as f32 - 1.0);
-        let start_x = (screen_width() - total_width) / 2.0;
+        let cell_size = self.layout.cell_size;
         
-        for (idx, block) in self.current_blocks.iter().enumerate() {
+        for idx in 0..self.engine.current_blocks.len() {
             // 计算每个方块的中心位置
-            let block_pos_x = start_x + block_size/2.0 + idx as f32 * (block_size + block_margin);
-            let block_pos_y = blocks_y;
+            let (block_pos, block_size) = self.tray_slot(idx);
             
             // 增加容错范围 - 使点击判定区域比实际方块大一些
             let tolerance_factor = 1.4; // 增加40%的判定区域
@@ -360,14 +511,17 @@ impl Game {
             
             // 创建扩大后的判定区域，保持中心点不变
             let block_rect = Rect::new(
-                block_pos_x - touch_width/2.0, 
-                block_pos_y - touch_height/2.0,
+                block_pos.x - touch_width/2.0, 
+                block_pos.y - touch_height/2.0,
                 touch_width, 
                 touch_height
             );
             
             if block_rect.contains(mouse_pos) {
                 self.drag_block_idx = Some(idx);
+                // 正在滑回的方块被重新拿起时直接从手指位置开始
+                self.flights.retain(|flight| flight.slot != Some(idx));
+                self.drag_scale = tween::Tween::new(0.0, 1.0, DRAG_SCALE_TIME, tween::Easing::BackOut);
                 
                 // 计算向上的偏移量 - 使方块在手指上方显示，但保持中心点对齐
                 let touch_offset_y = -cell_size * 2.0; // 向上偏移2个格子的距离
@@ -387,173 +541,217 @@ impl Game {
         }
     }
     
-    // 处理拖拽结束 - 这个方法已在update_game中实现
-    fn end_drag(&mut self) {
-        // 注意：这个方法不再被使用，逻辑已转移到update_game函数中
-        // 保留此方法是为了避免大量修改代码结构
-    }
-    
-    // 检查游戏结束条件
-    fn check_game_over(&self) -> bool {
-        // 检查每个当前方块是否有位置可以放置
-        for block in &self.current_blocks {
-            for y in 0..8 {
-                for x in 0..8 {
-                    if self.grid.can_place_block(block, x, y) {
-                        return false; // 找到至少一个可放置位置
+    // 在指定网格位置放置方块，处理消除特效和最高分
+    // 索引无效或位置不可放置时返回false
+    fn place_block_at(&mut self, block_idx: usize, grid_x: i32, grid_y: i32) -> bool {
+        let Some(block) = self.engine.current_blocks.get(block_idx).cloned() else {
+            return false;
+        };
+        let block_color = theme::current().block_color(block.color);
+        // 消除前的网格（包括刚放下的方块），供消除动画使用
+        let mut board = self.engine.grid.clone();
+        let Some(outcome) = self.engine.place(block_idx, grid_x, grid_y) else {
+            return false;
+        };
+        board.place_block(&block, grid_x, grid_y);
+        
+        // 候选方块被移除后，后面的槽位前移
+        self.flights.retain_mut(|flight| match flight.slot {
+            Some(slot) if slot == block_idx => false,
+            Some(slot) if slot > block_idx => {
+                flight.slot = Some(slot - 1);
+                true
+            },
+            _ => true,
+        });
+        
+        let grid = self.layout.grid;
+        
+        // 菜单演示模式保持安静
+        if !self.attract_mode {
+            self.effects.play_place_sound();
+            self.effects.play_clear_sound(outcome.lines_cleared, outcome.combo);
+        }
+        
+        if outcome.lines_cleared > 0 {
+            // 规则引擎已经清除了格子，由特效播放消除过渡，不阻塞下一次放置
+            self.effects.show_line_clear(&outcome.cleared_rows, &outcome.cleared_cols, &board);
+            
+            // 只在实际被消除的格子位置显示粒子效果
+            // 行列交点只添加一次粒子效果
+            let mut centroid = Vec2::ZERO;
+            let mut cleared_cells = 0;
+            for (y, &row_cleared) in outcome.cleared_rows.iter().enumerate() {
+                for (x, &col_cleared) in outcome.cleared_cols.iter().enumerate() {
+                    if row_cleared || col_cleared {
+                        let effect_pos = self.layout.cell_center(x as i32, y as i32);
+                        // 使用方块的颜色
+                        self.effects.show_clear_effect(effect_pos.x, effect_pos.y, block_color);
+                        centroid += effect_pos;
+                        cleared_cells += 1;
                     }
                 }
             }
+            
+            // 得分从被消除格子的中心飘起
+            centroid /= cleared_cells.max(1) as f32;
+            self.effects.show_score_popup(outcome.points, centroid.x, centroid.y);
+            
+            // 一次消除多行时在网格上方显示夸奖
+            let center_x = grid.center().x;
+            self.effects.show_praise(outcome.lines_cleared, center_x, grid.y + grid.h * 0.3);
+            
+            // 大消除时震屏和闪屏，菜单演示模式不震
+            if !self.attract_mode {
+                self.effects.show_impact(outcome.lines_cleared, outcome.combo);
+            }
+            
+            // 连续消除时显示连击横幅，高连击时横幅带星星特效
+            if outcome.combo >= 2 {
+                self.effects.show_combo_banner(outcome.combo, center_x, grid.center().y);
+            }
+            
+            // 自动游玩的分数不计入最高分
+            if self.autoplay.is_none() {
+                host::emit(host::HostEvent::ScoreChanged(self.engine.score));
+                
+                // 更新最高分
+                if self.engine.score > self.save_data.high_score {
+                    self.save_data.high_score = self.engine.score;
+                    self.save_data.save();
+                }
+            }
         }
-        true // 没有可放置位置，游戏结束
+        true
     }
-}
-
-// 绘制函数
-fn draw_game(game: &Game) {
-    // 获取DPI缩放比例
-    let dpi_scale = get_dpi_scale();
     
-    // 修改窗口背景为深灰色
-    clear_background(Color::new(0.2, 0.2, 0.22, 1.0));
-    
-    // 绘制游戏内容
-    // 计算网格尺寸和位置，考虑DPI缩放
-    let grid_size = screen_width() * 0.9;
-    let cell_size = grid_size / 8.0;
-    let grid_offset_x = (screen_width() - grid_size) / 2.0;
-    
-    // 根据屏幕大小动态计算顶部偏移
-    let grid_offset_y = screen_height() * 0.07;
-
-    // 绘制游戏标题，字体大小根据DPI缩放
-    draw_chinese_text("逆向俄罗斯方块", 
-             screen_width() / 2.0,
-             grid_offset_y / 2.0, 
-             20.0 * dpi_scale, // 字体大小乘以DPI缩放
-             WHITE);
-    
-    // 绘制游戏网格背景
-    draw_rectangle(
-        grid_offset_x - 5.0,
-        grid_offset_y - 5.0,
-        grid_size + 10.0,
-        grid_size + 10.0,
-        Color::new(0.1, 0.1, 0.12, 1.0)
-    );
-    
-    // 添加细边框 - 在高DPI设备上更清晰
-    let border_width = 2.0 * dpi_scale;
-    draw_rectangle_lines(
-        grid_offset_x - 5.0,
-        grid_offset_y - 5.0,
-        grid_size + 10.0,
-        grid_size + 10.0,
-        border_width,
-        Color::new(0.3, 0.3, 0.3, 1.0)
-    );
-    
-    // 绘制游戏网格
-    game.grid.draw(grid_offset_x, grid_offset_y, cell_size);
-    
-    // 更新粒子效果系统
-    game.effects.draw();
+    // 候选区第idx个方块的锚点和格子大小
+    fn tray_anchor(&self, idx: usize) -> (Vec2, f32) {
+        let (pos, block_size) = self.tray_slot(idx);
+        (pos, block_size / 5.0)
+    }
     
-    // 显示游戏分数
-    let score_y = grid_offset_y + grid_size + 23.0;
-    draw_chinese_text(
-        &format!("分数: {}", game.score), 
-        40.0, // 向右调整，更美观
-        score_y, 
-        15.0 * dpi_scale, 
-        WHITE
-    );
+    // 拖拽中方块当前的锚点和格子大小，随拿起动画从候选区尺寸放大到网格尺寸
+    fn drag_anchor(&self, block_idx: usize, pos: Vec2, cell_size: f32) -> Option<(Vec2, f32)> {
+        let block = self.engine.current_blocks.get(block_idx)?;
+        let tray_cell = self.tray_anchor(block_idx).1;
+        let cell = tween::Lerp::lerp(tray_cell, cell_size, self.drag_scale.value());
+        // 以方块几何中心（手指位置）为基准缩放
+        let center = pos - top_left_cell_offset(block, cell_size);
+        Some((center - block_center(block) * cell, cell))
+    }
     
-    // 显示最高分
-    draw_chinese_text(
-        &format!("最高分: {}", game.save_data.high_score), 
-        screen_width() - 100.0, // 向右调整，更美观
-        score_y, 
-        15.0 * dpi_scale, 
-        WHITE
-    );
+    // 更新拖拽和松手动画
+    fn update_animations(&mut self, dt: f32) {
+        self.drag_scale.update(dt);
+        let block_count = self.engine.current_blocks.len();
+        self.flights.retain_mut(|flight| {
+            flight.progress.update(dt);
+            !flight.progress.finished() && flight.slot.is_none_or(|slot| slot < block_count)
+        });
+    }
     
-    // 绘制分隔线
-    // 检测小屏幕并调整间距
-    let is_small_screen = screen_height() < 600.0;
-    let spacing = if is_small_screen { 20.0 } else { 30.0 };
-    let separator_y = grid_offset_y + grid_size + 15.0 + spacing;
-    let bottom_area_top = separator_y + (if is_small_screen { 2.0 } else { 5.0 });
-    draw_line(
-        10.0,
-        separator_y,
-        screen_width() - 10.0,
-        separator_y,
-        2.0 * dpi_scale, // 线宽度也随DPI缩放
-        Color::new(0.3, 0.3, 0.3, 1.0)
-    );
+    // 结束演示或当前对局，回到菜单
+    fn return_to_menu(&mut self) {
+        self.autoplay = None;
+        self.attract_mode = false;
+        self.menu_idle_time = 0.0;
+        self.drag_block_idx = None;
+        self.drag_pos = None;
+        self.state = GameState::Menu;
+    }
+
+}
+
+// 暂停菜单按钮的位置
+fn pause_menu_buttons(layout: &layout::Layout) -> Vec<(Rect, PauseAction)> {
+    layout.pause_menu_buttons(PauseAction::ALL.len()).into_iter().zip(PauseAction::ALL).collect()
+}
+
+// 设置界面每一行的位置：(选项, 整行, 减小按钮, 增大按钮)
+fn settings_rows(layout: &layout::Layout) -> Vec<(SettingItem, Rect, Rect, Rect)> {
+    layout
+        .settings_rows(SettingItem::ALL.len())
+        .into_iter()
+        .zip(SettingItem::ALL)
+        .map(|((row, minus, plus), item)| (item, row, minus, plus))
+        .collect()
+}
+
+// 音量滑条的轨道：位于减小和增大按钮之间
+fn slider_track_rect(row: Rect, minus: Rect, plus: Rect) -> Rect {
+    let left = minus.x + minus.w + 8.0;
+    let right = plus.x - 8.0;
+    Rect::new(left, row.y + row.h / 2.0 - 4.0, (right - left).max(1.0), 8.0)
+}
+
+// 绘制带文字的按钮，鼠标悬停时高亮
+fn draw_button(list: &mut DrawList, rect: Rect, label: &str, font_size: f32) {
+    let hovered = rect.contains(mouse_position().into());
+    let fill = if hovered {
+        Color::new(0.35, 0.35, 0.4, 1.0)
+    } else {
+        Color::new(0.22, 0.22, 0.26, 1.0)
+    };
+    list.rect(rect, fill);
+    list.rect_lines(rect, 2.0, Color::new(0.5, 0.5, 0.55, 1.0));
+    list.text(label, rect.center(), TextStyle::new(font_size, WHITE).wrap(rect.w - 8.0));
+}
+
+// 生成一帧的绘制命令，由 run_game 交给绘制后端
+fn draw_game(game: &Game, list: &mut DrawList) {
+    // 文字大小和线宽乘以界面缩放
+    let ui_scale = display::ui_scale();
     
-    // 绘制下方区域的背景
-    let bottom_area_top = separator_y + (if is_small_screen { 2.0 } else { 5.0 });
-    // 确保底部区域至少有屏幕高度的一定比例
-    let min_bottom_height = screen_height() * 0.2; // 至少占屏幕高度的20%
-    let bottom_area_height = (screen_height() - bottom_area_top).max(min_bottom_height);
-    draw_rectangle(
-        0.0,
-        bottom_area_top,
-        screen_width(),
-        bottom_area_height,
-        Color::new(0.15, 0.15, 0.17, 1.0)
-    );
+    // 对局界面的颜色来自当前主题
+    let theme = theme::current();
     
-    // 绘制可选方块区域的标题
-    draw_chinese_text(
-        "可拖拽方块", 
-        screen_width() / 2.0, // 居中显示
-        bottom_area_top + (if is_small_screen { 15.0 } else { 25.0 }), 
-        20.0 * dpi_scale, // 字体大小乘以DPI缩放
-        WHITE
-    );
+    // 绘制游戏内容，位置都来自布局
+    let layout = &game.layout;
+    let cell_size = layout.cell_size;
+    let screen = layout.screen;
     
-    // 绘制当前可选方块 - 在竖屏模式下水平排列
-    // 计算垂直位置，使方块位于底部区域的中间
-    let blocks_y = bottom_area_top + bottom_area_height / 2.0; // 可拖拽方块位于底部区域的垂直中心
+    // 震屏时网格整体偏移绘制，命中检测仍使用未偏移的位置
+    let board = layout.grid.point() + game.effects.shake_offset();
     
-    // 计算方块布局 - 根据最大方块数量(blocks_per_generation)确定尺寸，而非当前方块数量
-    // 这样即使放置了方块，剩余方块的大小也不会突然变化
-    let max_block_size = cell_size * 4.0; // 最大方块尺寸
-    let block_size = if game.blocks_per_generation <= 2 {
-        max_block_size // 对于1-2个最大方块，使用最大尺寸
-    } else {
-        // 对于更多方块，减小尺寸以适应屏幕
-        // 考虑屏幕大小，在小屏幕上进一步减小尺寸
-        let width_factor = if is_small_screen { 0.80 } else { 0.85 };
-        (screen_width() * width_factor) / (game.blocks_per_generation as f32 * 1.2)
-    };
+    // 标题、网格背景、分数和候选区
+    scene::draw_hud(list, layout, &theme, ui_scale, board, game.engine.score, game.save_data.high_score);
     
-    let block_margin = block_size * 0.2; // 方块之间的间距根据方块大小缩放
-    let total_width = block_size * game.current_blocks.len() as f32 + block_margin * (game.current_blocks.len() as f32 - 1.0);
-    let start_x = (screen_width() - total_width) / 2.0;
+    // 网格线、网格中的方块和消除动画
+    game.engine.grid.draw(list, board, cell_size, &theme);
+    game.effects.draw_line_clears(list, &game.engine.grid, board, cell_size, &theme);
     
-    for (idx, block) in game.current_blocks.iter().enumerate() {
-        let block_pos_x = start_x + block_size/2.0 + idx as f32 * (block_size + block_margin);
-        let block_pos_y = blocks_y;
-        
-        // 绘制方块 - 根据方块尺寸调整单元格大小
-        let cell_scale = block_size / (cell_size * 5.0); // 调整单元格大小与方块大小的比例
-        for &(dx, dy) in &block.cells {
-            let x = block_pos_x + dx as f32 * cell_size * cell_scale;
-            let y = block_pos_y + dy as f32 * cell_size * cell_scale;
-            draw_cube_block(x - cell_size * cell_scale / 2.0, y - cell_size * cell_scale / 2.0, 
-                          cell_size * cell_scale, block.color);
+    // 绘制当前可选方块，竖屏时水平排列，横屏时纵向排列
+    list.set_layer(Layer::Blocks);
+    for (idx, block) in game.engine.current_blocks.iter().enumerate() {
+        // 拖拽中和正在滑回的方块另外绘制
+        if game.drag_block_idx == Some(idx) || game.flights.iter().any(|flight| flight.slot == Some(idx)) {
+            continue;
         }
+        let (anchor, cell) = game.tray_anchor(idx);
+        scene::draw_block_cells(list, block, anchor, cell, &theme);
+    }
+    
+    // 松手后滑回候选区或吸附到网格的方块
+    for flight in &game.flights {
+        let to = match flight.slot {
+            Some(slot) => game.tray_anchor(slot),
+            None => flight.to,
+        };
+        let t = flight.progress.value();
+        let anchor = tween::Lerp::lerp(flight.from.0, to.0, t);
+        let cell = tween::Lerp::lerp(flight.from.1, to.1, t);
+        scene::draw_block_cells(list, &flight.block, anchor, cell, &theme);
     }
     
+    // 粒子效果在方块之上
+    game.effects.draw(list);
+    
     // 绘制拖拽中的方块
     if let (Some(block_idx), Some(pos)) = (game.drag_block_idx, game.drag_pos) {
         // 确保索引有效
-        if block_idx < game.current_blocks.len() {
-            let block = &game.current_blocks[block_idx];
+        if block_idx < game.engine.current_blocks.len() {
+            let block = &game.engine.current_blocks[block_idx];
             
             // 找到最左上角的cell（最小x和y坐标的cell）
             let mut min_dx = i32::MAX;
@@ -567,460 +765,690 @@ fn draw_game(game: &Game) {
                 }
             }
             
-            // 计算方块的几何中心
-            let min_dx_all = block.cells.iter().map(|(dx, _)| *dx).min().unwrap_or(0);
-            let max_dx_all = block.cells.iter().map(|(dx, _)| *dx).max().unwrap_or(0);
-            let min_dy_all = block.cells.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
-            let max_dy_all = block.cells.iter().map(|(_, dy)| *dy).max().unwrap_or(0);
-            
-            let center_x = (min_dx_all + max_dx_all) as f32 / 2.0;
-            let center_y = (min_dy_all + max_dy_all) as f32 / 2.0;
-            
             // 计算左上角cell在网格中的坐标
             // pos现在是左上角cell的中心点
-            let grid_top_left_x = ((pos.x - grid_offset_x) / cell_size).floor();
-            let grid_top_left_y = ((pos.y - grid_offset_y) / cell_size).floor();
+            let (grid_top_left_x, grid_top_left_y) = layout.cell_at(pos);
             
             // 计算网格坐标（以左上角cell为基准）
-            let grid_x = grid_top_left_x as i32 - min_dx;
-            let grid_y = grid_top_left_y as i32 - min_dy;
+            let grid_x = grid_top_left_x - min_dx;
+            let grid_y = grid_top_left_y - min_dy;
             
             // 判断是否在有效网格范围内
-            let is_valid_pos = grid_x >= -1 && grid_x < 9 && grid_y >= -1 && grid_y < 9; // 扩大检测范围
+            let is_valid_pos = (-1..9).contains(&grid_x) && (-1..9).contains(&grid_y); // 扩大检测范围
             
             // 使用容错功能检查放置 - 仅用于预览
             let (can_place, corrected_x, corrected_y) = if is_valid_pos {
-                game.grid.can_place_block_with_tolerance(block, grid_x, grid_y, 1) // 1格容错距离
+                game.engine.grid.can_place_block_with_tolerance(block, grid_x, grid_y, 1) // 1格容错距离
             } else {
                 (false, grid_x, grid_y)
             };
             
             // 为所有单元格绘制预览
+            list.set_layer(Layer::Preview);
             for &(dx, dy) in &block.cells {
                 // 使用校正后的坐标绘制预览
-                let preview_x = grid_offset_x + (corrected_x + dx) as f32 * cell_size;
-                let preview_y = grid_offset_y + (corrected_y + dy) as f32 * cell_size;
+                let preview = Rect::new(
+                    board.x + (corrected_x + dx) as f32 * cell_size,
+                    board.y + (corrected_y + dy) as f32 * cell_size,
+                    cell_size,
+                    cell_size
+                );
                 
                 // 仅当预览位置在有效范围内时才绘制
-                if (corrected_x + dx) >= 0 && (corrected_x + dx) < 8 && (corrected_y + dy) >= 0 && (corrected_y + dy) < 8 {
+                if (0..8).contains(&(corrected_x + dx)) && (0..8).contains(&(corrected_y + dy)) {
                     // 根据能否放置绘制不同颜色
                     if can_place {
                         // 半透明绿色
-                        draw_rectangle(preview_x, preview_y, cell_size, cell_size, 
-                                       Color::new(0.2, 0.8, 0.2, 0.4));
+                        list.rect(preview, Color::new(0.2, 0.8, 0.2, 0.4));
                         
                         // 如果是校正后的位置，添加闪烁边框提示用户
                         if corrected_x != grid_x || corrected_y != grid_y {
-                            let pulse = (get_time() * 5.0).sin() * 0.5 + 0.5;
-                            draw_rectangle_lines(
-                                preview_x, preview_y, cell_size, cell_size,
-                                2.0 * dpi_scale, // 线宽考虑DPI缩放
+                            let pulse = (game.effects.time() * 5.0).sin() * 0.5 + 0.5;
+                            list.rect_lines(
+                                preview,
+                                2.0 * ui_scale, // 线宽考虑界面缩放
                                 Color::new(1.0, 1.0, 1.0, 0.5 + 0.3 * pulse as f32)
                             );
                         }
                     } else {
                         // 半透明红色
-                        draw_rectangle(preview_x, preview_y, cell_size, cell_size, 
-                                       Color::new(0.8, 0.2, 0.2, 0.4));
+                        list.rect(preview, Color::new(0.8, 0.2, 0.2, 0.4));
                     }
                 }
             }
             
-            // 在网格上拖动时绘制方块
-            for &(dx, dy) in &block.cells {
-                // 使用实际鼠标位置(pos)来绘制拖动中的方块
-                let rel_dx = dx - min_dx; // 相对于左上角的偏移
-                let rel_dy = dy - min_dy;
-                
-                // 计算每个方块单元的实际位置
-                let x = pos.x + rel_dx as f32 * cell_size;
-                let y = pos.y + rel_dy as f32 * cell_size;
-                
-                // 绘制立体方块
-                draw_cube_block(x - cell_size/2.0, y - cell_size/2.0, cell_size, block.color);
+            // 在网格上拖动时绘制方块，拿起时从候选区尺寸放大
+            // 拖拽中的方块在预览之上
+            if let Some((anchor, cell)) = game.drag_anchor(block_idx, pos, cell_size) {
+                list.set_layer(Layer::Drag);
+                scene::draw_block_cells(list, block, anchor, cell, &theme);
             }
             
         }
     }
     
-    // 绘制菜单/游戏结束界面
+    // 得分飘字、夸奖和连击横幅
+    game.effects.draw_popups(list, screen);
+    
+    // 自动游玩提示
+    list.set_layer(Layer::Overlay);
+    if let Some(auto) = &game.autoplay {
+        let hint = if game.attract_mode {
+            tr("hud.demo")
+        } else {
+            let bot = tr(match auto.bot.strength {
+                bot::BotStrength::Random => "bot.random",
+                bot::BotStrength::Greedy => "bot.greedy",
+                bot::BotStrength::Lookahead => "bot.lookahead",
+            });
+            tr_args("hud.autoplay", &[("bot", &bot), ("game", &(auto.games_played + 1))])
+        };
+        list.text(
+            &hint,
+            Vec2::new(screen.x / 2.0, screen.y - 8.0),
+            TextStyle::new(14.0 * ui_scale, theme.accent)
+                .valign(VAlign::Bottom)
+                .wrap(screen.x * 0.95)
+        );
+    }
+    
+    // 对局中的暂停按钮（演示模式下点击任意位置即返回菜单，不显示）
+    if game.state == GameState::Playing && !game.attract_mode {
+        let rect = game.layout.pause_button();
+        let bar_w = rect.w * 0.18;
+        let bar_h = rect.h * 0.55;
+        let bar_y = rect.y + (rect.h - bar_h) / 2.0;
+        list.rect(rect, Color::new(0.25, 0.25, 0.3, 1.0));
+        list.rect(Rect::new(rect.x + rect.w * 0.28, bar_y, bar_w, bar_h), WHITE);
+        list.rect(Rect::new(rect.x + rect.w * 0.54, bar_y, bar_w, bar_h), WHITE);
+    }
+    
+    // 绘制菜单/暂停/设置/游戏结束界面
+    list.set_layer(Layer::Menu);
+    let full_screen = Rect::new(0.0, 0.0, screen.x, screen.y);
     match game.state {
         GameState::Menu => {
             // 绘制半透明背景
-            draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.99));
+            list.rect(full_screen, Color::new(0.0, 0.0, 0.0, 0.99));
             
             // 绘制大标题
-            draw_chinese_text("逆向俄罗斯方块", 
-                     screen_width() / 2.0, 
-                     screen_height() / 3.0, 
-                     40.0 * dpi_scale, 
-                     WHITE);
+            list.text(
+                &tr("app.title"),
+                Vec2::new(screen.x / 2.0, screen.y / 3.0),
+                TextStyle::new(40.0 * ui_scale, WHITE)
+                    .shadow(Vec2::new(0.0, 3.0), Color::new(1.0, 0.5, 0.1, 0.6))
+            );
             
             // 绘制开始提示
-            draw_chinese_text("点击开始游戏", 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0, 
-                     25.0 * dpi_scale, 
-                     Color::new(1.0, 0.8, 0.2, 1.0));
+            list.text(
+                &tr("menu.start"),
+                Vec2::new(screen.x / 2.0, screen.y / 2.0),
+                TextStyle::new(25.0 * ui_scale, Color::new(1.0, 0.8, 0.2, 1.0))
+            );
             
             // 绘制最高分
-            draw_chinese_text(&format!("最高分: {}", game.save_data.high_score), 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0 + 80.0, 
-                     22.0 * dpi_scale, 
-                     Color::new(0.2, 0.8, 1.0, 1.0));
+            list.text(
+                &tr_args("menu.high_score", &[("score", &game.save_data.high_score)]),
+                Vec2::new(screen.x / 2.0, screen.y / 2.0 + 80.0),
+                TextStyle::new(22.0 * ui_scale, Color::new(0.2, 0.8, 1.0, 1.0))
+            );
             
             // 绘制难度选择
-            let mode_text = if game.easy_mode { "简单模式" } else { "普通模式" };
-            draw_chinese_text(mode_text, 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0 + 120.0, 
-                     22.0 * dpi_scale, 
-                     if game.easy_mode { GREEN } else { YELLOW });
+            let mode_text = tr(if game.easy_mode { "menu.mode.easy" } else { "menu.mode.normal" });
+            list.text(
+                &mode_text,
+                Vec2::new(screen.x / 2.0, screen.y / 2.0 + 120.0),
+                TextStyle::new(22.0 * ui_scale, if game.easy_mode { GREEN } else { YELLOW })
+            );
+            
+            // 操作提示依次向下排列，换行时后面的提示随之下移
+            let hints = [
+                (tr("menu.hint.difficulty"), WHITE),
+                (tr("menu.hint.chances"), GRAY),
+                (tr("menu.hint.autoplay"), GRAY),
+            ];
+            let mut hint_y = screen.y / 2.0 + 140.0;
+            for (hint, color) in &hints {
+                let style = TextStyle::new(18.0 * ui_scale, *color)
+                    .valign(VAlign::Top)
+                    .wrap(screen.x * 0.9);
+                let pos = Vec2::new(screen.x / 2.0, hint_y);
+                hint_y = text_rect(hint, pos.x, pos.y, &style).bottom() + 8.0;
+                list.text(hint, pos, style);
+            }
             
-            draw_chinese_text("按空格键切换游戏难度", 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0 + 150.0, 
-                     18.0 * dpi_scale, 
-                     WHITE);
+            draw_button(list, game.layout.menu_settings_button(), &tr("pause.settings"), 18.0 * ui_scale);
+        },
+        GameState::Paused => {
+            // 对局画面上叠加半透明遮罩
+            list.rect(full_screen, Color::new(0.0, 0.0, 0.0, 0.6));
+            
+            list.text(
+                &tr("pause.title"),
+                Vec2::new(screen.x / 2.0, screen.y / 4.0),
+                TextStyle::new(30.0 * ui_scale, WHITE)
+            );
+            
+            for (rect, action) in pause_menu_buttons(&game.layout) {
+                draw_button(list, rect, &action.label(), 20.0 * ui_scale);
+            }
+        },
+        GameState::Settings => {
+            list.rect(full_screen, Color::new(0.0, 0.0, 0.0, 0.95));
+            
+            list.text(
+                &tr("settings.title"),
+                Vec2::new(screen.x / 2.0, screen.y / 8.0),
+                TextStyle::new(30.0 * ui_scale, WHITE)
+            );
+            
+            for (item, row, minus, plus) in settings_rows(&game.layout) {
+                list.rect(row, Color::new(0.15, 0.15, 0.17, 1.0));
+                list.text(
+                    &item.label(),
+                    Vec2::new(row.x + 12.0, row.y + row.h / 2.0),
+                    TextStyle::new(18.0 * ui_scale, WHITE)
+                        .align(HAlign::Left)
+                        .wrap(minus.x - row.x - 20.0)
+                );
+                draw_button(list, minus, "<", 18.0 * ui_scale);
+                draw_button(list, plus, ">", 18.0 * ui_scale);
+                if item.is_slider() {
+                    // 滑条：轨道、已填充部分和拖动手柄
+                    let track = slider_track_rect(row, minus, plus);
+                    let filled = track.w * game.slider_fraction(item);
+                    list.rect(track, Color::new(0.3, 0.3, 0.35, 1.0));
+                    list.rect(Rect::new(track.x, track.y, filled, track.h), Color::new(1.0, 0.8, 0.2, 1.0));
+                    list.circle(Vec2::new(track.x + filled, track.y + track.h / 2.0), track.h, WHITE);
+                    continue;
+                }
+                list.text(
+                    &game.setting_value(item),
+                    Vec2::new((minus.x + minus.w + plus.x) / 2.0, row.y + row.h / 2.0),
+                    TextStyle::new(18.0 * ui_scale, Color::new(1.0, 0.8, 0.2, 1.0))
+                );
+            }
             
-            draw_chinese_text("1/2:调整方块概率 3/4:调整方块数量", 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0 + 180.0, 
-                     18.0 * dpi_scale, 
-                     GRAY);
+            draw_button(list, game.layout.settings_back_button(), &tr("settings.back"), 20.0 * ui_scale);
         },
         GameState::GameOver => {
             // 绘制半透明背景
-            draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.99));
+            list.rect(full_screen, Color::new(0.0, 0.0, 0.0, 0.99));
             
             // 绘制游戏结束文本
-            draw_chinese_text("游戏结束", 
-                     screen_width() / 2.0, 
-                     screen_height() / 3.0, 
-                     30.0 * dpi_scale, 
-                     WHITE);
+            list.text(
+                &tr("game_over.title"),
+                Vec2::new(screen.x / 2.0, screen.y / 3.0),
+                TextStyle::new(30.0 * ui_scale, WHITE)
+            );
             
             // 绘制最终得分
-            draw_chinese_text(&format!("最终得分: {}", game.score), 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0, 
-                     25.0 * dpi_scale, 
-                     Color::new(1.0, 0.8, 0.2, 1.0));
+            list.text(
+                &tr_args("game_over.final_score", &[("score", &game.engine.score)]),
+                Vec2::new(screen.x / 2.0, screen.y / 2.0),
+                TextStyle::new(25.0 * ui_scale, Color::new(1.0, 0.8, 0.2, 1.0))
+            );
             
             // 绘制最高分
-            let new_record = game.score > game.save_data.high_score;
+            let new_record = game.engine.score > game.high_score_at_start;
             let high_score_text = if new_record {
-                format!("新纪录! {}", game.score)
+                tr_args("game_over.new_record", &[("score", &game.engine.score)])
             } else {
-                format!("最高分: {}", game.save_data.high_score)
+                tr_args("game_over.high_score", &[("score", &game.save_data.high_score)])
             };
             
-            draw_chinese_text(&high_score_text, 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0 + 40.0, 
-                     22.0 * dpi_scale, 
-                     if new_record { Color::new(1.0, 0.5, 0.0, 1.0) } else { Color::new(0.2, 0.8, 1.0, 1.0) });
+            list.text(
+                &high_score_text,
+                Vec2::new(screen.x / 2.0, screen.y / 2.0 + 40.0),
+                TextStyle::new(22.0 * ui_scale, if new_record { Color::new(1.0, 0.5, 0.0, 1.0) } else { Color::new(0.2, 0.8, 1.0, 1.0) })
+            );
             
             // 绘制重新开始提示
-            draw_chinese_text("点击重新开始", 
-                     screen_width() / 2.0, 
-                     screen_height() / 2.0 + 100.0, 
-                     25.0 * dpi_scale, 
-                     WHITE);
+            list.text(
+                &tr("game_over.restart"),
+                Vec2::new(screen.x / 2.0, screen.y / 2.0 + 100.0),
+                TextStyle::new(25.0 * ui_scale, WHITE)
+            );
         },
-        _ => {}
+        GameState::Playing => {}
     }
 }
 
-// 更新游戏状态
 fn update_game(game: &mut Game) {
-    // 更新粒子效果
-    game.effects.update(get_frame_time());
+    let frame_time = get_frame_time();
+    let dt = frame_time.min(MAX_FRAME_TIME);
+    
+    // 窗口移到另一台显示器或网页缩放后像素比会变化，逻辑屏幕大小随之改变
+    display::update();
+    game.effects.text_scale = display::ui_scale();
+    // 窗口大小改变后重新计算布局
+    game.refresh_layout();
+    
+    // 处理宿主页面的命令
+    for command in host::poll_commands() {
+        game.handle_host_command(command);
+    }
+    
+    // 网页版由桥接插件在页面隐藏或失去焦点（visibilitychange、blur）时发送暂停命令
+    // 原生平台没有焦点事件：miniquad的 window_minimized_event / window_restored_event
+    // 由macroquad内部处理，不会转发给游戏，这里只能做卡顿暂停（见 STALL_PAUSE_FRAME_GAP）
+    // 启动和读取文件后的几帧跳过判断，避免把加载时间当成卡顿
+    if game.stall_check_delay > 0 {
+        game.stall_check_delay -= 1;
+    } else {
+        #[cfg(not(target_arch = "wasm32"))]
+        if frame_time > STALL_PAUSE_FRAME_GAP {
+            game.pause();
+        }
+    }
     
-    // 检测按空格键切换难度模式
-    if is_key_pressed(KeyCode::Space) {
-        game.easy_mode = !game.easy_mode;
+    // 背景音乐：对局中（包括暂停）使用对局列表，其余界面和演示模式使用菜单列表
+    if game.audio_started {
+        let context = if game.in_game() && !game.attract_mode {
+            music::MusicContext::Gameplay
+        } else {
+            music::MusicContext::Menu
+        };
+        game.music.set_context(context);
+        game.music.update(dt, game.save_data.settings.effective_music_volume());
     }
     
-    // 调整简单方块概率 (±10%)
-    if is_key_pressed(KeyCode::Key1) && game.simple_block_chance > 0 {
-        game.simple_block_chance = (game.simple_block_chance - 10).max(0);
-        game.standard_block_chance = ((100 - game.simple_block_chance) as f32 * 0.8) as i32;
+    // 暂停和设置界面不更新对局、特效和计时器
+    match game.state {
+        GameState::Paused => {
+            update_pause_menu(game);
+            return;
+        },
+        GameState::Settings => {
+            update_settings(game);
+            return;
+        },
+        _ => {}
     }
-    if is_key_pressed(KeyCode::Key2) && game.simple_block_chance < 100 {
-        game.simple_block_chance = (game.simple_block_chance + 10).min(100);
-        game.standard_block_chance = ((100 - game.simple_block_chance) as f32 * 0.8) as i32;
+    
+    // 更新粒子效果和方块动画
+    game.effects.update(dt);
+    game.update_animations(dt);
+    
+    // 快捷键：空格切换难度模式，1/2调整简单方块概率，3/4调整每次生成的方块数量
+    let shortcuts = [
+        (KeyCode::Space, SettingItem::Difficulty, 1),
+        (KeyCode::Key1, SettingItem::SimpleChance, -1),
+        (KeyCode::Key2, SettingItem::SimpleChance, 1),
+        (KeyCode::Key3, SettingItem::BlocksPerGeneration, -1),
+        (KeyCode::Key4, SettingItem::BlocksPerGeneration, 1),
+    ];
+    for (key, item, delta) in shortcuts {
+        if is_key_pressed(key) {
+            game.adjust_setting(item, delta);
+        }
     }
     
-    // 调整每次生成的方块数量 (1-5)
-    if is_key_pressed(KeyCode::Key3) && game.blocks_per_generation > 1 {
-        game.blocks_per_generation -= 1;
-        if game.current_blocks.len() > game.blocks_per_generation {
-            game.current_blocks.pop();
+    // F2开关自动游玩（用于长时间测试），F3切换机器人强度
+    if is_key_pressed(KeyCode::F2) {
+        if game.autoplay.is_some() && !game.attract_mode {
+            game.autoplay = None;
+            game.drag_block_idx = None;
+            game.drag_pos = None;
+        } else {
+            game.autoplay = Some(AutoPlay::new(bot::BotStrength::Lookahead, game.seed));
+            game.attract_mode = false;
+            if game.state != GameState::Playing {
+                game.start_new_game();
+            }
         }
     }
-    if is_key_pressed(KeyCode::Key4) && game.blocks_per_generation < 5 {
-        game.blocks_per_generation += 1;
-        if game.state == GameState::Playing && game.current_blocks.len() < game.blocks_per_generation {
-            game.current_blocks.push(block::BlockShape::random_with_chances(
-                game.simple_block_chance, game.standard_block_chance));
+    if is_key_pressed(KeyCode::F3) {
+        if let Some(auto) = game.autoplay.as_mut() {
+            auto.bot.strength = auto.bot.strength.next();
         }
     }
     
     match game.state {
         GameState::Menu => {
+            game.menu_idle_time += dt;
+            if get_last_key_pressed().is_some() {
+                game.menu_idle_time = 0.0;
+            }
+            
             if is_mouse_button_pressed(MouseButton::Left) {
-                game.state = GameState::Playing;
-                game.grid = grid::Grid::new();
-                game.score = 0;
-                game.combo = 0;
-                game.generate_blocks();
+                // 浏览器只允许在用户操作之后播放声音
+                game.audio_started = true;
+                if game.layout.menu_settings_button().contains(mouse_position().into()) {
+                    game.open_settings();
+                } else {
+                    game.start_new_game();
+                }
+            } else if game.menu_idle_time > ATTRACT_IDLE_TIME {
+                // 菜单空闲一段时间后由机器人演示游戏
+                game.autoplay = Some(AutoPlay::new(bot::BotStrength::Lookahead, game.seed));
+                game.attract_mode = true;
+                game.start_new_game();
             }
         },
         GameState::Playing => {
-            // 获取鼠标位置
-            let mouse_pos: Vec2 = mouse_position().into();
-            
-            // 计算网格位置和大小
-            let grid_size = screen_width() * 0.9;
-            let cell_size = grid_size / 8.0;
-            let grid_offset_x = (screen_width() - grid_size) / 2.0;
-            let grid_offset_y = screen_height() * 0.07;
+            if game.attract_mode {
+                // 演示模式下任何点击或按键都返回菜单
+                if is_mouse_button_pressed(MouseButton::Left) || get_last_key_pressed().is_some() {
+                    game.return_to_menu();
+                    return;
+                }
+            } else if is_key_pressed(KeyCode::Escape)
+                || (is_mouse_button_pressed(MouseButton::Left)
+                    && game.layout.pause_button().contains(mouse_position().into()))
+            {
+                game.pause();
+                return;
+            }
             
-            // 检测小屏幕并调整间距
-            let is_small_screen = screen_height() < 600.0;
-            let spacing = if is_small_screen { 20.0 } else { 30.0 };
-            let separator_y = grid_offset_y + grid_size + 15.0 + spacing;
-            let bottom_area_top = separator_y + (if is_small_screen { 2.0 } else { 5.0 });
+            if game.autoplay.is_some() {
+                update_autoplay(game, dt);
+            } else {
+                update_drag(game);
+            }
             
-            // 处理拖拽逻辑
-            if is_mouse_button_pressed(MouseButton::Left) {
-                // 只能从底部区域开始拖动
-                if mouse_pos.y > separator_y {
-                    game.start_drag(mouse_pos);
+            // 检查游戏结束
+            if game.engine.is_game_over() {
+                game.state = GameState::GameOver;
+                if !game.attract_mode {
+                    game.effects.play_game_over_sound();
+                }
+                if let Some(auto) = game.autoplay.as_mut() {
+                    auto.games_played += 1;
+                    auto.timer = 0.0;
+                    println!("自动游玩: 第{}局结束, 分数 {}", auto.games_played, game.engine.score);
+                } else {
+                    host::emit(host::HostEvent::GameOver(game.engine.score));
+                    if game.engine.score > game.high_score_at_start {
+                        host::emit(host::HostEvent::HighScore(game.engine.score));
+                    }
                 }
             }
-            
-            // 处理拖动中的方块
-            if is_mouse_button_down(MouseButton::Left) && game.drag_block_idx.is_some() {
-                if let Some(block_idx) = game.drag_block_idx {
-                    // 检查索引是否有效
-                    if block_idx < game.current_blocks.len() {
-                        let block = &game.current_blocks[block_idx];
-                        
-                        // 找到最左上角的cell（最小x和y坐标的cell）
-                        let mut min_dx = i32::MAX;
-                        let mut min_dy = i32::MAX;
-                        for &(dx, dy) in &block.cells {
-                            if dx < min_dx {
-                                min_dx = dx;
-                            }
-                            if dy < min_dy {
-                                min_dy = dy;
-                            }
-                        }
-                        
-                        // 核心改动：应用偏移量使方块位于手指上方
-                        let adjusted_pos = Vec2::new(
-                            mouse_pos.x + game.drag_offset.x,
-                            mouse_pos.y + game.drag_offset.y
-                        );
-                        
-                        // 计算方块左上角cell的中心点
-                        // 先计算方块几何中心
-                        let min_dx_all = block.cells.iter().map(|(dx, _)| *dx).min().unwrap_or(0);
-                        let max_dx_all = block.cells.iter().map(|(dx, _)| *dx).max().unwrap_or(0);
-                        let min_dy_all = block.cells.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
-                        let max_dy_all = block.cells.iter().map(|(_, dy)| *dy).max().unwrap_or(0);
-                        let center_x = (min_dx_all + max_dx_all) as f32 / 2.0;
-                        let center_y = (min_dy_all + max_dy_all) as f32 / 2.0;
-                        
-                        // 从方块中心到左上角cell的偏移
-                        let offset_to_top_left_x = (min_dx as f32 - center_x) * cell_size;
-                        let offset_to_top_left_y = (min_dy as f32 - center_y) * cell_size;
-                        
-                        // 计算左上角cell的中心点坐标
-                        let top_left_cell_center = Vec2::new(
-                            adjusted_pos.x + offset_to_top_left_x,
-                            adjusted_pos.y + offset_to_top_left_y
-                        );
-                        
-                        game.drag_pos = Some(top_left_cell_center);
-                    } else {
-                        // 索引无效，重置拖拽状态
-                        game.drag_block_idx = None;
-                        game.drag_pos = None;
+        },
+        GameState::GameOver => {
+            if let Some(auto) = game.autoplay.as_mut() {
+                auto.timer += dt;
+                if game.attract_mode {
+                    if auto.timer > AUTOPLAY_RESTART_DELAY || is_mouse_button_pressed(MouseButton::Left) {
+                        game.return_to_menu();
                     }
-                } else {
-                    // 这个分支不应该发生，但以防万一
-                    game.drag_pos = None;
+                } else if auto.timer > AUTOPLAY_RESTART_DELAY {
+                    // 长时间测试：自动开始下一局
+                    auto.timer = 0.0;
+                    game.start_new_game();
                 }
+            } else if is_mouse_button_pressed(MouseButton::Left) {
+                game.return_to_menu();
             }
-            
-            // 在鼠标释放时处理方块放置
-            if is_mouse_button_released(MouseButton::Left) && game.drag_block_idx.is_some() {
-                if let Some(block_idx) = game.drag_block_idx {
-                    if let Some(pos) = game.drag_pos {
-                        let block = &game.current_blocks[block_idx];
-                        
-                        // 计算网格大小和位置
-                        let grid_size = screen_width() * 0.9;
-                        let cell_size = grid_size / 8.0;
-                        let grid_offset_x = (screen_width() - grid_size) / 2.0;
-                        let grid_offset_y = screen_height() * 0.07;
-                        
-                        // 找到最左上角的cell（最小x和y坐标的cell）
-                        let mut min_dx = i32::MAX;
-                        let mut min_dy = i32::MAX;
-                        for &(dx, dy) in &block.cells {
-                            if dx < min_dx {
-                                min_dx = dx;
-                            }
-                            if dy < min_dy {
-                                min_dy = dy;
-                            }
+        },
+        GameState::Paused | GameState::Settings => {}
+    }
+}
+
+// 暂停菜单：Esc继续，点击按钮执行对应操作
+fn update_pause_menu(game: &mut Game) {
+    if is_key_pressed(KeyCode::Escape) {
+        game.resume();
+        return;
+    }
+    if !is_mouse_button_pressed(MouseButton::Left) {
+        return;
+    }
+    
+    let mouse_pos: Vec2 = mouse_position().into();
+    let clicked = pause_menu_buttons(&game.layout)
+        .into_iter()
+        .find(|(rect, _)| rect.contains(mouse_pos))
+        .map(|(_, action)| action);
+    match clicked {
+        Some(PauseAction::Resume) => game.resume(),
+        Some(PauseAction::Restart) => game.restart(),
+        Some(PauseAction::Settings) => game.open_settings(),
+        Some(PauseAction::QuitToMenu) => game.return_to_menu(),
+        None => {}
+    }
+}
+
+// 设置界面：点击左右按钮调整选项，Esc或返回按钮回到之前的界面
+fn update_settings(game: &mut Game) {
+    let mouse_pos: Vec2 = mouse_position().into();
+    let clicked = is_mouse_button_pressed(MouseButton::Left);
+    
+    if is_key_pressed(KeyCode::Escape) || (clicked && game.layout.settings_back_button().contains(mouse_pos)) {
+        game.state = game.settings_return;
+        // 从设置回到菜单时重新计算空闲时间，避免刚关闭设置就进入演示
+        game.menu_idle_time = 0.0;
+        return;
+    }
+    
+    // 拖动音量滑条，松开时保存
+    if let Some(item) = game.slider_drag {
+        if let Some((_, row, minus, plus)) = settings_rows(&game.layout).into_iter().find(|(i, ..)| *i == item) {
+            let track = slider_track_rect(row, minus, plus);
+            game.set_slider_fraction(item, (mouse_pos.x - track.x) / track.w);
+        }
+        if !is_mouse_button_down(MouseButton::Left) {
+            game.slider_drag = None;
+            game.save_data.save();
+        }
+        return;
+    }
+    if !clicked {
+        return;
+    }
+    
+    for (item, row, minus, plus) in settings_rows(&game.layout) {
+        if minus.contains(mouse_pos) {
+            game.adjust_setting(item, -1);
+        } else if plus.contains(mouse_pos) {
+            game.adjust_setting(item, 1);
+        } else if item.is_slider() {
+            // 点击轨道所在的区域开始拖动
+            let track = slider_track_rect(row, minus, plus);
+            let hit_area = Rect::new(track.x - 8.0, row.y, track.w + 16.0, row.h);
+            if hit_area.contains(mouse_pos) {
+                game.slider_drag = Some(item);
+            }
+        }
+    }
+}
+
+// 处理玩家的拖拽输入
+fn update_drag(game: &mut Game) {
+    // 获取鼠标位置
+    let mouse_pos: Vec2 = mouse_position().into();
+    
+    let layout = game.layout;
+    let cell_size = layout.cell_size;
+    
+    // 处理拖拽逻辑
+    if is_mouse_button_pressed(MouseButton::Left) {
+        // 只能从候选区开始拖动
+        if layout.in_tray(mouse_pos) {
+            game.start_drag(mouse_pos);
+        }
+    }
+    
+    // 处理拖动中的方块
+    if is_mouse_button_down(MouseButton::Left) && game.drag_block_idx.is_some() {
+        if let Some(block_idx) = game.drag_block_idx {
+            // 检查索引是否有效
+            if block_idx < game.engine.current_blocks.len() {
+                let block = &game.engine.current_blocks[block_idx];
+                
+                // 核心改动：应用偏移量使方块位于手指上方
+                let adjusted_pos = Vec2::new(
+                    mouse_pos.x + game.drag_offset.x,
+                    mouse_pos.y + game.drag_offset.y
+                );
+                
+                // 计算左上角cell的中心点坐标
+                game.drag_pos = Some(adjusted_pos + top_left_cell_offset(block, cell_size));
+            } else {
+                // 索引无效，重置拖拽状态
+                game.drag_block_idx = None;
+                game.drag_pos = None;
+            }
+        } else {
+            // 这个分支不应该发生，但以防万一
+            game.drag_pos = None;
+        }
+    }
+    
+    // 在鼠标释放时处理方块放置
+    if is_mouse_button_released(MouseButton::Left) && game.drag_block_idx.is_some() {
+        if let (Some(block_idx), Some(pos)) = (game.drag_block_idx, game.drag_pos) {
+            // 拖拽过程中方块数量可能被改变，需要检查索引
+            if let Some(block) = game.engine.current_blocks.get(block_idx).cloned() {
+                // 松手时方块的锚点和格子大小，作为松手动画的起点
+                let from = game.drag_anchor(block_idx, pos, cell_size).unwrap_or((pos, cell_size));
+                let mut placed = false;
+                
+                // 找到最左上角的cell（最小x和y坐标的cell）
+                let min_dx = block.cells.iter().map(|(dx, _)| *dx).min().unwrap_or(0);
+                let min_dy = block.cells.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
+                
+                // 计算左上角cell在网格中的坐标
+                // pos现在是左上角cell的中心点
+                let (grid_top_left_x, grid_top_left_y) = layout.cell_at(pos);
+                
+                // 计算网格坐标（以左上角cell为基准）
+                let grid_x = grid_top_left_x - min_dx;
+                let grid_y = grid_top_left_y - min_dy;
+                
+                // 检查并处理方块放置 - 使用容错版本
+                // 先判断是否在扩展的有效范围内
+                let is_near_valid = (-1..9).contains(&grid_x) && (-1..9).contains(&grid_y);
+                
+                if is_near_valid {
+                    // 使用容错功能找到合适的放置位置
+                    let (can_place, corrected_x, corrected_y) = 
+                        game.engine.grid.can_place_block_with_tolerance(&block, grid_x, grid_y, 1);
+                    
+                    if can_place {
+                        // 如果位置被校正了，播放提示音效或视觉效果
+                        if corrected_x != grid_x || corrected_y != grid_y {
+                            println!("位置已自动校正: 从({},{})到({},{})", 
+                                     grid_x, grid_y, corrected_x, corrected_y);
+                            // TODO: 添加声音或特效提示
                         }
                         
-                        // 计算左上角cell在网格中的坐标
-                        // pos现在是左上角cell的中心点
-                        let grid_top_left_x = ((pos.x - grid_offset_x) / cell_size).floor();
-                        let grid_top_left_y = ((pos.y - grid_offset_y) / cell_size).floor();
-                        
-                        // 计算网格坐标（以左上角cell为基准）
-                        let grid_x = grid_top_left_x as i32 - min_dx;
-                        let grid_y = grid_top_left_y as i32 - min_dy;
-                        
-                        // 检查并处理方块放置 - 使用容错版本
-                        // 先判断是否在扩展的有效范围内
-                        let is_near_valid = grid_x >= -1 && grid_x < 9 && grid_y >= -1 && grid_y < 9;
-                        
-                        if is_near_valid {
-                            // 使用容错功能找到合适的放置位置
-                            let (can_place, corrected_x, corrected_y) = 
-                                game.grid.can_place_block_with_tolerance(block, grid_x, grid_y, 1);
-                            
-                            if can_place {
-                                // 执行放置 - 使用校正后的位置
-                                game.grid.place_block(block, corrected_x, corrected_y);
-                                
-                                // 如果位置被校正了，播放提示音效或视觉效果
-                                if corrected_x != grid_x || corrected_y != grid_y {
-                                    println!("位置已自动校正: 从({},{})到({},{})", 
-                                             grid_x, grid_y, corrected_x, corrected_y);
-                                    // TODO: 添加声音或特效提示
-                                }
-                                
-                                // 首先记录当前哪些行和列是满的（将被消除）
-                                let mut filled_rows = [false; 8];
-                                let mut filled_cols = [false; 8];
-                                
-                                // 检查哪些行是满的
-                                for y in 0..8 {
-                                    let mut row_filled = true;
-                                    for x in 0..8 {
-                                        if game.grid.cells[y][x].is_none() {
-                                            row_filled = false;
-                                            break;
-                                        }
-                                    }
-                                    filled_rows[y] = row_filled;
-                                }
-                                
-                                // 检查哪些列是满的
-                                for x in 0..8 {
-                                    let mut col_filled = true;
-                                    for y in 0..8 {
-                                        if game.grid.cells[y][x].is_none() {
-                                            col_filled = false;
-                                            break;
-                                        }
-                                    }
-                                    filled_cols[x] = col_filled;
-                                }
-                                
-                                // 检查消除行和列
-                                let (rows_cleared, cols_cleared) = game.grid.check_and_clear();
-                                let cleared = rows_cleared + cols_cleared;
-                                
-                                if cleared > 0 {
-                                    // 只在实际被消除的格子位置显示粒子效果
-                                    // 对于被消除的行
-                                    for y in 0..8 {
-                                        if filled_rows[y] {
-                                            for x in 0..8 {
-                                                let effect_x = grid_offset_x + x as f32 * cell_size + cell_size/2.0;
-                                                let effect_y = grid_offset_y + y as f32 * cell_size + cell_size/2.0;
-                                                // 使用方块的颜色
-                                                game.effects.show_clear_effect(effect_x, effect_y, block.color);
-                                            }
-                                        }
-                                    }
-                                    
-                                    // 对于被消除的列
-                                    for x in 0..8 {
-                                        if filled_cols[x] {
-                                            for y in 0..8 {
-                                                // 避免重复在行列交点添加两次粒子效果
-                                                if !filled_rows[y] {
-                                                    let effect_x = grid_offset_x + x as f32 * cell_size + cell_size/2.0;
-                                                    let effect_y = grid_offset_y + y as f32 * cell_size + cell_size/2.0;
-                                                    // 使用方块的颜色
-                                                    game.effects.show_clear_effect(effect_x, effect_y, block.color);
-                                                }
-                                            }
-                                        }
-                                    }
-                                    
-                                    // 高combo时显示特殊效果
-                                    if game.combo >= 2 {
-                                        let combo_x = screen_width() / 2.0;
-                                        let combo_y = grid_offset_y + grid_size / 2.0;
-                                        game.effects.show_combo_effect(game.combo, combo_x, combo_y);
-                                    }
-                                    
-                                    // 更新分数和连击
-                                    game.combo += 1;
-                                    game.score += cleared * 100 * game.combo;
-                                    
-                                    // 更新最高分
-                                    if game.score > game.save_data.high_score {
-                                        game.save_data.high_score = game.score;
-                                        game.save_data.save();
-                                    }
-                                } else {
-                                    // 重置连击
-                                    game.combo = 0;
-                                }
-                                
-                                // 移除已使用的方块
-                                game.current_blocks.remove(block_idx);
-                                
-                                // 如果没有方块了，生成新的
-                                if game.current_blocks.is_empty() {
-                                    game.generate_blocks();
-                                }
-                            }
-                        }
+                        // 执行放置 - 使用校正后的位置
+                        placed = game.place_block_at(block_idx, corrected_x, corrected_y);
                     }
                     
-                    // 重置拖拽状态
-                    game.drag_block_idx = None;
-                    game.drag_pos = None;
+                    if placed {
+                        // 吸附到目标格子
+                        let to = layout.cell_center(corrected_x, corrected_y);
+                        game.flights.push(PieceFlight {
+                            block: block.clone(),
+                            slot: None,
+                            from,
+                            to: (to, cell_size),
+                            progress: tween::Tween::new(0.0, 1.0, SNAP_TIME, tween::Easing::QuadOut),
+                        });
+                    }
+                }
+                
+                if !placed {
+                    // 位置无效，滑回候选区
+                    game.flights.push(PieceFlight {
+                        block,
+                        slot: Some(block_idx),
+                        from,
+                        to: from,
+                        progress: tween::Tween::new(0.0, 1.0, RETURN_TIME, tween::Easing::CubicOut),
+                    });
                 }
             }
-            
-            // 检查游戏结束
-            if game.check_game_over() {
-                game.state = GameState::GameOver;
+        }
+        
+        // 重置拖拽状态
+        game.drag_block_idx = None;
+        game.drag_pos = None;
+    }
+}
+
+// 方块几何中心相对(0,0)格子中心的位置（以格子为单位）
+fn block_center(block: &block::BlockShape) -> Vec2 {
+    let min_dx = block.cells.iter().map(|(dx, _)| *dx).min().unwrap_or(0);
+    let max_dx = block.cells.iter().map(|(dx, _)| *dx).max().unwrap_or(0);
+    let min_dy = block.cells.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
+    let max_dy = block.cells.iter().map(|(_, dy)| *dy).max().unwrap_or(0);
+    Vec2::new((min_dx + max_dx) as f32 / 2.0, (min_dy + max_dy) as f32 / 2.0)
+}
+
+// 从方块几何中心到左上角cell中心的偏移
+fn top_left_cell_offset(block: &block::BlockShape, cell_size: f32) -> Vec2 {
+    let min_dx = block.cells.iter().map(|(dx, _)| *dx).min().unwrap_or(0);
+    let max_dx = block.cells.iter().map(|(dx, _)| *dx).max().unwrap_or(0);
+    let min_dy = block.cells.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
+    let max_dy = block.cells.iter().map(|(_, dy)| *dy).max().unwrap_or(0);
+    let center_x = (min_dx + max_dx) as f32 / 2.0;
+    let center_y = (min_dy + max_dy) as f32 / 2.0;
+    Vec2::new(
+        (min_dx as f32 - center_x) * cell_size,
+        (min_dy as f32 - center_y) * cell_size
+    )
+}
+
+// 机器人自动游玩：思考停顿后选择一步，再用拖拽动画把方块移到目标位置并放置
+fn update_autoplay(game: &mut Game, dt: f32) {
+    let Some(mut auto) = game.autoplay.take() else {
+        return;
+    };
+    auto.timer += dt;
+    
+    let layout = game.layout;
+    let cell_size = layout.cell_size;
+    
+    match auto.current {
+        None => {
+            if auto.timer >= AUTOPLAY_THINK_TIME {
+                if let Some(m) = auto.bot.choose_move(&game.engine.grid, &game.engine.current_blocks) {
+                    let block = &game.engine.current_blocks[m.block_idx];
+                    let (slot_center, _) = game.tray_slot(m.block_idx);
+                    auto.from = slot_center + top_left_cell_offset(block, cell_size);
+                    auto.current = Some(m);
+                    auto.timer = 0.0;
+                    game.drag_block_idx = Some(m.block_idx);
+                    game.drag_pos = Some(auto.from);
+                }
             }
         },
-        GameState::GameOver => {
-            if is_mouse_button_pressed(MouseButton::Left) {
-                game.state = GameState::Menu;
+        Some(m) => {
+            match game.engine.current_blocks.get(m.block_idx) {
+                Some(block) => {
+                    // 目标位置：左上角cell所在网格格子的中心
+                    let min_dx = block.cells.iter().map(|(dx, _)| *dx).min().unwrap_or(0);
+                    let min_dy = block.cells.iter().map(|(_, dy)| *dy).min().unwrap_or(0);
+                    let target = layout.cell_center(m.grid_x + min_dx, m.grid_y + min_dy);
+                    
+                    // 平滑插值，模拟手指拖动
+                    let t = (auto.timer / AUTOPLAY_DRAG_TIME).min(1.0);
+                    let eased = t * t * (3.0 - 2.0 * t);
+                    game.drag_pos = Some(auto.from.lerp(target, eased));
+                    
+                    if t >= 1.0 {
+                        game.place_block_at(m.block_idx, m.grid_x, m.grid_y);
+                        game.drag_block_idx = None;
+                        game.drag_pos = None;
+                        auto.current = None;
+                        auto.timer = 0.0;
+                    }
+                },
+                None => {
+                    // 方块数量在拖动中被改变，放弃这一步
+                    game.drag_block_idx = None;
+                    game.drag_pos = None;
+                    auto.current = None;
+                    auto.timer = 0.0;
+                }
             }
         }
     }
+    
+    game.autoplay = Some(auto);
 }
 
 // macroquad窗口配置函数
@@ -1032,7 +1460,7 @@ fn window_conf() -> Conf {
         high_dpi: true,  // 保留高DPI支持
         fullscreen: false,
         sample_count: 1,  // 移除抗锯齿，使用默认值1
-        window_resizable: false,
+        window_resizable: true,  // 横屏时候选区移到网格右侧
         ..Default::default()
     }
 }
@@ -1044,33 +1472,43 @@ async fn main() {
 
 // 主要游戏逻辑函数
 async fn run_game() {
-    // 显示设备信息和DPI缩放
-    let dpi_scale = get_dpi_scale();
-    println!("设备信息: 屏幕大小 {}x{}, DPI缩放: {}", screen_width(), screen_height(), dpi_scale);
-    
-    // iOS设备相关日志
-    #[cfg(target_os = "ios")]
-    println!("在iOS设备上运行，使用3.0倍DPI缩放");
+    // 显示设备信息和像素比
+    display::update();
+    println!("设备信息: 屏幕大小 {}x{}, 像素比: {}", screen_width(), screen_height(), display::pixel_ratio());
     
     // 使用嵌入的字体数据加载字体，而不是从文件系统加载
-    match load_ttf_font_from_bytes(CHINESE_FONT_DATA) {
-        Ok(font) => {
-            *CHINESE_FONT.lock().unwrap() = Some(font);
-            println!("成功加载中文字体");
-        },
-        Err(err) => {
-            println!("无法加载中文字体: {:?}", err);
-            // 在WASM环境中不触发panic
-            #[cfg(target_arch = "wasm32")]
-            println!("在WASM环境中继续运行，将使用默认字体");
-        }
-    }
+    text::load_embedded_font();
+    text::load_locale_fonts().await;
     
     let mut game = Game::new();
+    display::set_user_scale(game.save_data.settings.ui_scale);
+    game.effects.text_scale = display::ui_scale();
+    game.effects.reduce_motion = game.save_data.settings.reduce_motion;
+    locale::set_locale(game.save_data.settings.language);
+    theme::load_custom_theme().await;
+    if !theme::set_theme(&game.save_data.settings.theme) {
+        println!("找不到主题 {}，使用默认主题", game.save_data.settings.theme);
+    }
+    theme::set_colorblind(game.save_data.settings.colorblind);
+    text::set_theme_font(theme::current().font.as_deref());
+    skin::load_skins().await;
+    if !skin::set_skin(&game.save_data.settings.skin) {
+        println!("找不到方块皮肤 {}，使用默认皮肤", game.save_data.settings.skin);
+    }
+    game.effects.sounds = audio::Sounds::load().await;
+    game.apply_audio_settings();
+    game.music = music::Music::load().await;
+    let mut renderer = gpu::GpuRenderer::new();
     
     loop {
+        // 按需加载字体：切换到需要额外字体的语言时（加载完成前显示英文），或需要子集中没有的字时
+        if text::load_pending_fonts().await {
+            game.stall_check_delay = STALL_CHECK_DELAY_FRAMES;
+        }
         update_game(&mut game);
-        draw_game(&game);
+        let mut list = DrawList::new();
+        draw_game(&game, &mut list);
+        renderer.draw_list(&list);
         
         next_frame().await
     }
@@ -0,0 +1,132 @@
+// 背景音乐：菜单和对局各有一个播放列表，曲目循环播放一段时间后切到列表中的下一首
+// 切换曲目或场景时新旧曲目交叉淡入淡出
+// 曲目在启动时由 synth::music_loop 合成，不需要打包音频文件
+use macroquad::audio::{load_sound_from_bytes, play_sound, set_sound_volume, stop_sound, PlaySoundParams, Sound};
+
+use crate::synth;
+
+/// 交叉淡入淡出的时长（秒）
+const CROSSFADE_TIME: f32 = 1.5;
+/// 每首曲目循环几遍后切到下一首
+const LOOPS_PER_TRACK: f32 = 4.0;
+
+/// 当前场景，决定使用哪个播放列表
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MusicContext {
+    Menu,
+    Gameplay,
+}
+
+struct Track {
+    sound: Sound,
+    duration: f32,  // 一遍的时长（秒）
+}
+
+// 正在播放（或正在淡出）的曲目
+struct Voice {
+    track: usize,
+    level: f32,   // 淡入淡出进度，0.0-1.0
+    target: f32,  // 淡入为1.0，淡出为0.0
+}
+
+#[derive(Default)]
+pub struct Music {
+    tracks: Vec<Track>,
+    menu_playlist: Vec<usize>,
+    gameplay_playlist: Vec<usize>,
+    voices: Vec<Voice>,
+    context: Option<MusicContext>,  // 尚未开始播放时为None
+    position: usize,                // 当前播放列表中的位置
+    elapsed: f32,                   // 当前曲目已播放的时间
+}
+
+impl Music {
+    /// 合成并加载所有曲目，无法加载的曲目跳过
+    pub async fn load() -> Self {
+        // (根音, 和弦进行, 速度, 所属场景)
+        let compositions: [(f32, &[[i32; 3]], f32, MusicContext); 3] = [
+            // C大调 I-vi-IV-V，舒缓
+            (130.81, &[[0, 4, 7], [9, 12, 16], [5, 9, 12], [7, 11, 14]], 72.0, MusicContext::Menu),
+            // A小调 i-VI-III-VII
+            (110.0, &[[0, 3, 7], [8, 12, 15], [3, 7, 10], [10, 14, 17]], 100.0, MusicContext::Gameplay),
+            // C大调 I-V-vi-IV
+            (130.81, &[[0, 4, 7], [7, 11, 14], [9, 12, 16], [5, 9, 12]], 108.0, MusicContext::Gameplay),
+        ];
+
+        let mut music = Music::default();
+        for (root, chords, bpm, context) in compositions {
+            let samples = synth::music_loop(root, chords, bpm);
+            let duration = samples.len() as f32 / synth::SAMPLE_RATE as f32;
+            match load_sound_from_bytes(&synth::to_wav(&samples)).await {
+                Ok(sound) => {
+                    let idx = music.tracks.len();
+                    music.tracks.push(Track { sound, duration });
+                    match context {
+                        MusicContext::Menu => music.menu_playlist.push(idx),
+                        MusicContext::Gameplay => music.gameplay_playlist.push(idx),
+                    }
+                }
+                Err(err) => println!("无法加载背景音乐: {:?}", err),
+            }
+        }
+        println!("已加载背景音乐: {}/{}", music.tracks.len(), compositions.len());
+        music
+    }
+
+    /// 切换场景，第一次调用时开始播放
+    /// 浏览器要求音频在用户操作之后才能播放，因此由玩家的第一次点击触发
+    pub fn set_context(&mut self, context: MusicContext) {
+        if self.context != Some(context) {
+            self.context = Some(context);
+            self.position = 0;
+            self.elapsed = 0.0;
+        }
+    }
+
+    /// 每帧更新：推进播放列表并处理淡入淡出，volume为音乐音量
+    pub fn update(&mut self, dt: f32, volume: f32) {
+        let Some(context) = self.context else { return };
+        let playlist = match context {
+            MusicContext::Menu => &self.menu_playlist,
+            MusicContext::Gameplay => &self.gameplay_playlist,
+        };
+        if playlist.is_empty() {
+            return;
+        }
+
+        // 当前曲目播放够了就切到列表中的下一首
+        let mut track = playlist[self.position % playlist.len()];
+        self.elapsed += dt;
+        if playlist.len() > 1 && self.elapsed >= self.tracks[track].duration * LOOPS_PER_TRACK {
+            self.position = (self.position + 1) % playlist.len();
+            self.elapsed = 0.0;
+            track = playlist[self.position];
+        }
+
+        // 目标曲目淡入，其他曲目淡出；目标曲目如果正在淡出则直接接着淡入
+        for voice in &mut self.voices {
+            voice.target = if voice.track == track { 1.0 } else { 0.0 };
+        }
+        if !self.voices.iter().any(|voice| voice.track == track) {
+            play_sound(&self.tracks[track].sound, PlaySoundParams { looped: true, volume: 0.0 });
+            self.voices.push(Voice { track, level: 0.0, target: 1.0 });
+        }
+
+        let step = dt / CROSSFADE_TIME;
+        let tracks = &self.tracks;
+        self.voices.retain_mut(|voice| {
+            voice.level = if voice.target > voice.level {
+                (voice.level + step).min(voice.target)
+            } else {
+                (voice.level - step).max(voice.target)
+            };
+            let sound = &tracks[voice.track].sound;
+            if voice.level <= 0.0 && voice.target <= 0.0 {
+                stop_sound(sound);
+                return false;
+            }
+            set_sound_volume(sound, voice.level * volume);
+            true
+        });
+    }
+}
@@ -0,0 +1,316 @@
+// 粒子系统：固定容量的粒子池 + 发射器描述
+// 发射器描述决定粒子怎样生成和变化（方向、速度、重力、阻力、颜色渐变、大小曲线、旋转、形状）
+// 一次性爆发的粒子直接生成，持续发射的发射器每帧按速率生成
+// 随机数来自粒子系统自己的种子，不影响玩法的随机序列，相同种子下画面逐帧一致
+use std::f32::consts::{PI, TAU};
+
+use macroquad::prelude::*;
+
+use crate::random::SimpleRandom;
+use crate::render::{BlendMode, DrawCommand, DrawList};
+use crate::tween::{Easing, Lerp};
+
+/// 粒子池容量，满了以后新粒子轮流覆盖最早的槽位，不再分配内存
+pub const MAX_PARTICLES: usize = 1024;
+
+/// 粒子形状
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParticleShape {
+    Square,
+    Circle,
+    Star,
+}
+
+/// 发射方式
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EmitMode {
+    /// 一次性发射 min..=max 个粒子
+    Burst { min: u32, max: u32 },
+    /// 在duration秒内每秒发射rate个粒子
+    Continuous { rate: f32, duration: f32 },
+}
+
+/// 发射器描述，成对的字段为 (最小值, 最大值)，每个粒子在其中随机取值
+#[derive(Clone, Copy, Debug)]
+pub struct EmitterDesc {
+    pub mode: EmitMode,
+    pub shape: ParticleShape,
+    pub direction: f32,        // 发射方向（弧度，0为向右，y轴向下）
+    pub spread: f32,           // 发射角度范围（弧度），以direction为中心，TAU为全方向
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    pub size: (f32, f32),
+    pub size_end: f32,         // 生命结束时的大小（相对初始大小）
+    pub size_easing: Easing,   // 大小从初始变化到size_end的曲线
+    pub spin: (f32, f32),      // 旋转角速度（弧度/秒）
+    pub gravity: f32,          // 向下的加速度（像素/秒²）
+    pub drag: f32,             // 阻力，每秒损失的速度比例
+    pub color_start: Color,
+    pub color_end: Color,      // 颜色随生命周期从color_start渐变到color_end
+    pub additive: bool,        // 叠加混合，重叠处更亮
+}
+
+impl Default for EmitterDesc {
+    fn default() -> Self {
+        EmitterDesc {
+            mode: EmitMode::Burst { min: 10, max: 10 },
+            shape: ParticleShape::Square,
+            direction: 0.0,
+            spread: TAU,
+            speed: (30.0, 80.0),
+            lifetime: (0.5, 1.5),
+            size: (3.0, 8.0),
+            size_end: 0.0,
+            size_easing: Easing::Linear,
+            spin: (0.0, 0.0),
+            gravity: 50.0,
+            drag: 0.0,
+            color_start: WHITE,
+            color_end: Color::new(1.0, 1.0, 1.0, 0.0),
+            additive: false,
+        }
+    }
+}
+
+// 单个粒子，生成时从发射器描述中复制所需的参数
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    rotation: f32,
+    spin: f32,
+    size: f32,
+    size_end: f32,
+    size_easing: Easing,
+    gravity: f32,
+    drag: f32,
+    color_start: Color,
+    color_end: Color,
+    shape: ParticleShape,
+    additive: bool,
+    age: f32,
+    lifetime: f32,
+}
+
+impl Particle {
+    fn new(desc: &EmitterDesc, position: Vec2, rng: &mut SimpleRandom) -> Self {
+        let mut random_in = |(min, max): (f32, f32)| rng.gen_range_f32(min, max);
+        let angle = desc.direction + random_in((-desc.spread / 2.0, desc.spread / 2.0));
+        let speed = random_in(desc.speed);
+        Particle {
+            position,
+            velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+            rotation: random_in((0.0, TAU)),
+            spin: random_in(desc.spin),
+            size: random_in(desc.size),
+            size_end: desc.size_end,
+            size_easing: desc.size_easing,
+            gravity: desc.gravity,
+            drag: desc.drag,
+            color_start: desc.color_start,
+            color_end: desc.color_end,
+            shape: desc.shape,
+            additive: desc.additive,
+            age: 0.0,
+            lifetime: random_in(desc.lifetime).max(0.01),
+        }
+    }
+
+    // 更新粒子状态，返回粒子是否存活
+    fn update(&mut self, dt: f32) -> bool {
+        self.velocity.y += self.gravity * dt;
+        self.velocity *= (1.0 - self.drag * dt).max(0.0);
+        self.position += self.velocity * dt;
+        self.rotation += self.spin * dt;
+        self.age += dt;
+        self.age < self.lifetime
+    }
+
+    fn draw(&self, list: &mut DrawList) {
+        let t = self.age / self.lifetime;
+        let color = Lerp::lerp(self.color_start, self.color_end, t);
+        let size = self.size * Lerp::lerp(1.0, self.size_end, self.size_easing.apply(t));
+        if size <= 0.0 || color.a <= 0.0 {
+            return;
+        }
+
+        match self.shape {
+            ParticleShape::Square => {
+                // 以粒子位置为中心旋转的正方形
+                let (sin, cos) = self.rotation.sin_cos();
+                let half = size / 2.0;
+                let corner = |x: f32, y: f32| self.position + Vec2::new(x * cos - y * sin, x * sin + y * cos);
+                let points = [corner(-half, -half), corner(half, -half), corner(half, half), corner(-half, half)];
+                list.push(DrawCommand::Quad { points, color });
+            },
+            ParticleShape::Circle => list.circle(self.position, size / 2.0, color),
+            ParticleShape::Star => draw_star(list, self.position, size / 2.0, self.rotation, color),
+        }
+    }
+}
+
+// 五角星：中间的五边形加五个尖角三角形，互不重叠，叠加混合时亮度均匀
+fn draw_star(list: &mut DrawList, center: Vec2, radius: f32, rotation: f32, color: Color) {
+    let inner_radius = radius * 0.4;
+    let point = |angle: f32, r: f32| center + Vec2::new(angle.cos(), angle.sin()) * r;
+    // 让尖角朝上
+    let base = rotation - PI / 2.0;
+    for i in 0..5 {
+        let outer = base + i as f32 * TAU / 5.0;
+        let left = point(outer - TAU / 10.0, inner_radius);
+        let right = point(outer + TAU / 10.0, inner_radius);
+        list.triangle(point(outer, radius), left, right, color);
+        list.triangle(center, left, right, color);
+    }
+}
+
+// 持续发射中的发射器
+struct Emitter {
+    desc: EmitterDesc,
+    position: Vec2,
+    remaining: f32,  // 剩余发射时间
+    pending: f32,    // 累计未发射的粒子数（小数部分留到下一帧）
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    next_overwrite: usize,  // 粒子池满时下一个被覆盖的槽位
+    emitters: Vec<Emitter>,
+    rng: SimpleRandom,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParticleSystem {
+    pub fn new() -> Self {
+        ParticleSystem {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            next_overwrite: 0,
+            emitters: Vec::new(),
+            rng: SimpleRandom::new(0),
+        }
+    }
+
+    /// 在指定位置按描述发射粒子
+    pub fn emit(&mut self, desc: &EmitterDesc, position: Vec2) {
+        match desc.mode {
+            EmitMode::Burst { min, max } => {
+                let count = self.rng.gen_range(min as i32, max as i32 + 1);
+                for _ in 0..count {
+                    let particle = Particle::new(desc, position, &mut self.rng);
+                    self.spawn(particle);
+                }
+            }
+            EmitMode::Continuous { duration, .. } => {
+                self.emitters.push(Emitter { desc: *desc, position, remaining: duration, pending: 0.0 });
+            }
+        }
+    }
+
+    fn spawn(&mut self, particle: Particle) {
+        if self.particles.len() < MAX_PARTICLES {
+            self.particles.push(particle);
+        } else {
+            self.particles[self.next_overwrite] = particle;
+            self.next_overwrite = (self.next_overwrite + 1) % MAX_PARTICLES;
+        }
+    }
+
+    /// 更新发射器和所有粒子
+    pub fn update(&mut self, dt: f32) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in &mut emitters {
+            let EmitMode::Continuous { rate, .. } = emitter.desc.mode else { continue };
+            emitter.pending += rate * dt.min(emitter.remaining);
+            emitter.remaining -= dt;
+            while emitter.pending >= 1.0 {
+                emitter.pending -= 1.0;
+                let particle = Particle::new(&emitter.desc, emitter.position, &mut self.rng);
+                self.spawn(particle);
+            }
+        }
+        emitters.retain(|emitter| emitter.remaining > 0.0);
+        self.emitters = emitters;
+
+        // 反向迭代以便安全移除元素
+        let mut i = self.particles.len();
+        while i > 0 {
+            i -= 1;
+            if !self.particles[i].update(dt) {
+                self.particles.swap_remove(i);
+            }
+        }
+    }
+
+    /// 把所有粒子加入绘制命令，普通粒子在下，叠加混合的粒子在上
+    pub fn draw(&self, list: &mut DrawList) {
+        for particle in self.particles.iter().filter(|p| !p.additive) {
+            particle.draw(list);
+        }
+        list.set_blend(BlendMode::Additive);
+        for particle in self.particles.iter().filter(|p| p.additive) {
+            particle.draw(list);
+        }
+        list.set_blend(BlendMode::Alpha);
+    }
+
+    /// 当前粒子数量
+    pub fn count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// 清除所有粒子和发射器，并用seed重新初始化随机数
+    pub fn reset(&mut self, seed: u64) {
+        self.particles.clear();
+        self.emitters.clear();
+        self.next_overwrite = 0;
+        self.rng = SimpleRandom::new(seed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: [f32; 6] = [0.016, 0.033, 0.008, 0.05, 0.017, 0.1];
+
+    // 一次爆发加一个持续发射器，按给定的帧时间序列运行
+    fn run(seed: u64, frames: &[f32]) -> ParticleSystem {
+        let mut system = ParticleSystem::new();
+        system.reset(seed);
+        let burst = EmitterDesc { mode: EmitMode::Burst { min: 5, max: 20 }, ..Default::default() };
+        let stream = EmitterDesc { mode: EmitMode::Continuous { rate: 90.0, duration: 0.2 }, drag: 0.5, ..Default::default() };
+        system.emit(&burst, Vec2::new(100.0, 100.0));
+        system.emit(&stream, Vec2::new(50.0, 20.0));
+        for &dt in frames {
+            system.update(dt);
+        }
+        // 随机数状态也要相同：运行后再发射一次
+        system.emit(&burst, Vec2::ZERO);
+        system
+    }
+
+    #[test]
+    fn same_seed_and_frames_give_same_particles() {
+        let a = run(42, &FRAMES);
+        let b = run(42, &FRAMES);
+        assert!(a.count() > 0);
+        assert_eq!(a.particles, b.particles);
+        assert_ne!(a.particles, run(43, &FRAMES).particles);
+    }
+
+    #[test]
+    fn particles_expire_and_pool_is_bounded() {
+        let mut system = ParticleSystem::new();
+        let burst = EmitterDesc { mode: EmitMode::Burst { min: 600, max: 600 }, lifetime: (0.5, 1.0), ..Default::default() };
+        system.emit(&burst, Vec2::ZERO);
+        system.emit(&burst, Vec2::ZERO);
+        assert_eq!(system.count(), MAX_PARTICLES);
+        system.update(1.0);
+        assert_eq!(system.count(), 0);
+    }
+}
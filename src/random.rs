@@ -1,5 +1,7 @@
 // 简单的随机数生成器，不依赖wasm-bindgen
 // 使用Xorshift算法，适用于WebAssembly环境
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
 
 /// 自定义随机数生成器
 pub struct SimpleRandom {
@@ -8,10 +10,15 @@ pub struct SimpleRandom {
 
 impl SimpleRandom {
     /// 使用种子创建新的随机数生成器
-    /// 如果种子为0，使用一个默认非零值
+    /// 种子先经过splitmix64打散，避免相邻的小种子产生相近的序列
     pub fn new(seed: u64) -> Self {
-        let seed = if seed == 0 { 0x853c49e6748fea9b } else { seed };
-        SimpleRandom { state: seed }
+        let mut z = seed.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        // Xorshift的状态不能为0
+        let state = if z == 0 { 0x853c49e6748fea9b } else { z };
+        SimpleRandom { state }
     }
     
     /// 创建使用当前时间作为种子的随机数生成器
@@ -35,7 +42,7 @@ impl SimpleRandom {
     /// 生成0到1之间的随机浮点数
     pub fn next_float(&mut self) -> f32 {
         // 将u64转换为0到1之间的f32
-        (self.next_u64() & 0x00_FFFF_FFFF) as f32 / 0x1_0000_0000 as f32
+        (self.next_u64() & 0x00_FFFF_FFFF) as f32 / 0x1_0000_0000u64 as f32
     }
     
     /// 生成指定范围内的随机整数
@@ -65,41 +72,30 @@ impl SimpleRandom {
 }
 
 // 提供一个全局随机数生成器
-static mut GLOBAL_RNG: Option<SimpleRandom> = None;
+static GLOBAL_RNG: Lazy<Mutex<Option<SimpleRandom>>> = Lazy::new(|| Mutex::new(None));
 
 /// 初始化全局随机数生成器
 pub fn init_global_rng() {
-    unsafe {
-        GLOBAL_RNG = Some(SimpleRandom::new_from_time());
-    }
+    *GLOBAL_RNG.lock().unwrap() = Some(SimpleRandom::new_from_time());
+}
+
+// 在全局随机数生成器上执行操作，必要时先初始化
+fn with_global_rng<R>(f: impl FnOnce(&mut SimpleRandom) -> R) -> R {
+    let mut rng = GLOBAL_RNG.lock().unwrap();
+    f(rng.get_or_insert_with(SimpleRandom::new_from_time))
 }
 
 /// 生成0到max之间的随机整数
 pub fn gen_range(min: i32, max: i32) -> i32 {
-    unsafe {
-        if GLOBAL_RNG.is_none() {
-            init_global_rng();
-        }
-        GLOBAL_RNG.as_mut().unwrap().gen_range(min, max)
-    }
+    with_global_rng(|rng| rng.gen_range(min, max))
 }
 
 /// 生成min到max之间的随机浮点数
 pub fn gen_range_f32(min: f32, max: f32) -> f32 {
-    unsafe {
-        if GLOBAL_RNG.is_none() {
-            init_global_rng();
-        }
-        GLOBAL_RNG.as_mut().unwrap().gen_range_f32(min, max)
-    }
+    with_global_rng(|rng| rng.gen_range_f32(min, max))
 }
 
 /// 从切片中随机选择一个元素
-pub fn choose<'a, T>(slice: &'a [T]) -> Option<&'a T> {
-    unsafe {
-        if GLOBAL_RNG.is_none() {
-            init_global_rng();
-        }
-        GLOBAL_RNG.as_mut().unwrap().choose(slice)
-    }
-} 
\ No newline at end of file
+pub fn choose<T>(slice: &[T]) -> Option<&T> {
+    with_global_rng(|rng| rng.choose(slice))
+}
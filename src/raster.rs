@@ -0,0 +1,425 @@
+// 软件光栅化：在CPU上把绘制命令画到内存中的画布并编码为PNG，不需要GPU和窗口
+// 用于在CI中生成棋盘截图；图形每像素4x4采样做抗锯齿，文字用fontdue光栅化内嵌的子集字体
+// 贴图皮肤需要GPU纹理，这里的方块总是使用程序生成的立体效果；叠加混合也在这里按同样的公式计算
+use std::collections::HashMap;
+use once_cell::sync::Lazy;
+
+use macroquad::prelude::*;
+
+use crate::render::{bevel_quads, block_glyph, BlendMode, DrawCommand, DrawList, RenderBackend};
+use crate::text::{self, TextStyle};
+
+// 每像素每个方向的采样数
+const SUBSAMPLES: usize = 4;
+
+// macroquad画圆使用的边数
+const CIRCLE_SIDES: usize = 20;
+
+// 内嵌字体和它的上升/下降高度（相对字号），无法解析时不绘制文字
+struct RasterFont {
+    font: fontdue::Font,
+    ascent: f32,
+    descent: f32,
+}
+
+static FONT: Lazy<Option<RasterFont>> = Lazy::new(|| {
+    let (data, ascent, descent) = text::embedded_font();
+    match fontdue::Font::from_bytes(data, fontdue::FontSettings::default()) {
+        Ok(font) => Some(RasterFont { font, ascent, descent }),
+        Err(err) => {
+            println!("软件光栅化无法解析内嵌字体: {}", err);
+            None
+        }
+    }
+});
+
+/// 内存中的RGBA画布，绘制时使用逻辑像素坐标，乘以scale得到画布像素
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    scale: f32,
+    pixels: Vec<[f32; 4]>,  // 非预乘的RGBA，从上到下逐行
+    blend: BlendMode,       // 当前命令的混合方式
+}
+
+impl Canvas {
+    /// 创建逻辑大小为size、像素比为scale的透明画布
+    pub fn new(size: Vec2, scale: f32) -> Self {
+        let width = (size.x * scale).round().max(1.0) as usize;
+        let height = (size.y * scale).round().max(1.0) as usize;
+        Canvas { width, height, scale, pixels: vec![[0.0; 4]; width * height], blend: BlendMode::Alpha }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+
+    /// 绘制一个命令
+    pub fn draw(&mut self, command: &DrawCommand) {
+        match command {
+            DrawCommand::Rect { rect, color } => self.fill_rect(*rect, *color),
+            DrawCommand::RectLines { rect, thickness, color } => {
+                // 线条在矩形内侧，宽度为thickness的一半
+                let t = (thickness / 2.0).min(rect.w / 2.0).min(rect.h / 2.0);
+                self.fill_rect(Rect::new(rect.x, rect.y, rect.w, t), *color);
+                self.fill_rect(Rect::new(rect.x, rect.bottom() - t, rect.w, t), *color);
+                self.fill_rect(Rect::new(rect.x, rect.y + t, t, rect.h - 2.0 * t), *color);
+                self.fill_rect(Rect::new(rect.right() - t, rect.y + t, t, rect.h - 2.0 * t), *color);
+            },
+            DrawCommand::Line { from, to, thickness, color } => {
+                let direction = *to - *from;
+                if direction.length_squared() > 0.0 {
+                    let normal = direction.normalize().perp() * (thickness / 2.0);
+                    self.fill_polygon(&[*from + normal, *to + normal, *to - normal, *from - normal], *color);
+                }
+            },
+            DrawCommand::Triangle { points, color } => self.fill_polygon(points, *color),
+            DrawCommand::Quad { points, color } => self.fill_polygon(points, *color),
+            DrawCommand::Circle { center, radius, color } => {
+                let points: Vec<Vec2> = (0..CIRCLE_SIDES).map(|i| *center + circle_point(i) * *radius).collect();
+                self.fill_polygon(&points, *color);
+            },
+            DrawCommand::CircleLines { center, radius, thickness, color } => {
+                // 每条边画成一段环形的四边形
+                let (inner, outer) = (radius - thickness / 2.0, radius + thickness / 2.0);
+                for i in 0..CIRCLE_SIDES {
+                    let (a, b) = (circle_point(i), circle_point(i + 1));
+                    let quad = [*center + a * outer, *center + b * outer, *center + b * inner, *center + a * inner];
+                    self.fill_polygon(&quad, *color);
+                }
+            },
+            DrawCommand::Text { text, pos, style } => self.draw_text(text, *pos, style),
+            DrawCommand::Block { rect, index, color, bevel, glyph } => {
+                for (quad, color) in bevel_quads(*rect, *color, bevel) {
+                    self.fill_polygon(&quad, color);
+                }
+                if *glyph {
+                    for command in block_glyph(*rect, *color, *index) {
+                        self.draw(&command);
+                    }
+                }
+            },
+        }
+    }
+
+    // 把颜色按覆盖率混合到像素上
+    fn blend(&mut self, x: usize, y: usize, color: Color, coverage: f32) {
+        let alpha = color.a * coverage;
+        if alpha <= 0.0 {
+            return;
+        }
+        let pixel = &mut self.pixels[y * self.width + x];
+        if self.blend == BlendMode::Additive {
+            // 与GPU相同：源颜色×源透明度加到原有颜色上
+            let base = pixel[3];
+            let out_alpha = (base + alpha).min(1.0);
+            for (channel, source) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+                *channel = ((*channel * base + source * alpha) / out_alpha).min(1.0);
+            }
+            pixel[3] = out_alpha;
+            return;
+        }
+        // 原有颜色的权重为 原透明度 × (1 - alpha)
+        let out_alpha = alpha + pixel[3] * (1.0 - alpha);
+        let keep = out_alpha - alpha;
+        for (channel, source) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+            *channel = (source * alpha + *channel * keep) / out_alpha;
+        }
+        pixel[3] = out_alpha;
+    }
+
+    // 画布像素范围内、覆盖区域 [min, max) 的像素范围
+    fn pixel_range(&self, min: Vec2, max: Vec2) -> Option<(usize, usize, usize, usize)> {
+        let x0 = min.x.floor().max(0.0) as usize;
+        let y0 = min.y.floor().max(0.0) as usize;
+        let x1 = (max.x.ceil().max(0.0) as usize).min(self.width);
+        let y1 = (max.y.ceil().max(0.0) as usize).min(self.height);
+        (x0 < x1 && y0 < y1).then_some((x0, y0, x1, y1))
+    }
+
+    // 轴对齐的矩形，按面积精确计算边缘像素的覆盖率
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let min = rect.point() * self.scale;
+        let max = (rect.point() + rect.size()) * self.scale;
+        let Some((x0, y0, x1, y1)) = self.pixel_range(min, max) else { return };
+        for y in y0..y1 {
+            let cover_y = (max.y.min(y as f32 + 1.0) - min.y.max(y as f32)).clamp(0.0, 1.0);
+            for x in x0..x1 {
+                let cover_x = (max.x.min(x as f32 + 1.0) - min.x.max(x as f32)).clamp(0.0, 1.0);
+                self.blend(x, y, color, cover_x * cover_y);
+            }
+        }
+    }
+
+    // 凸多边形（顶点顺时针或逆时针均可），覆盖率为落在多边形内的采样点比例
+    fn fill_polygon(&mut self, points: &[Vec2], color: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let points: Vec<Vec2> = points.iter().map(|&p| p * self.scale).collect();
+        let min = points.iter().fold(Vec2::splat(f32::MAX), |acc, &p| acc.min(p));
+        let max = points.iter().fold(Vec2::splat(f32::MIN), |acc, &p| acc.max(p));
+        let Some((x0, y0, x1, y1)) = self.pixel_range(min, max) else { return };
+
+        // 按顶点的环绕方向判断边的内侧，面积为0时不绘制
+        let area: f32 = (0..points.len()).map(|i| points[i].perp_dot(points[(i + 1) % points.len()])).sum();
+        if area.abs() < 1e-6 {
+            return;
+        }
+        let orientation = area.signum();
+        let edges: Vec<(Vec2, Vec2)> = (0..points.len())
+            .map(|i| (points[i], points[(i + 1) % points.len()] - points[i]))
+            .collect();
+        let inside = |p: Vec2| edges.iter().all(|&(start, edge)| edge.perp_dot(p - start) * orientation >= 0.0);
+
+        let step = 1.0 / SUBSAMPLES as f32;
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let mut hits = 0;
+                for sy in 0..SUBSAMPLES {
+                    for sx in 0..SUBSAMPLES {
+                        let sample = Vec2::new(x as f32 + (sx as f32 + 0.5) * step, y as f32 + (sy as f32 + 0.5) * step);
+                        if inside(sample) {
+                            hits += 1;
+                        }
+                    }
+                }
+                if hits > 0 {
+                    self.blend(x, y, color, hits as f32 / (SUBSAMPLES * SUBSAMPLES) as f32);
+                }
+            }
+        }
+    }
+
+    // 与 text::draw_text_styled 相同的排版：换行、对齐、投影和描边
+    fn draw_text(&mut self, text: &str, pos: Vec2, style: &TextStyle) {
+        let Some(raster) = FONT.as_ref() else { return };
+        if style.font_size < 1.0 {
+            return;
+        }
+        let font_size = style.font_size;
+        let measure = |s: &str| s.chars().map(|c| raster.font.metrics(c, font_size).advance_width).sum::<f32>();
+        let layout = text::layout_lines(text, style, raster.ascent * font_size, raster.descent * font_size, measure);
+        let top = text::block_origin(&layout, pos.x, pos.y, style).y;
+
+        // 同一段文字的字形只光栅化一次
+        let pixel_size = font_size * self.scale;
+        let mut glyphs: HashMap<char, (fontdue::Metrics, Vec<u8>)> = HashMap::new();
+        let mut draw_pass = |canvas: &mut Canvas, offset: Vec2, color: Color| {
+            for (i, (line, width)) in layout.lines.iter().enumerate() {
+                let baseline = (top + layout.ascent + i as f32 * layout.line_height + offset.y) * canvas.scale;
+                let mut pen = (text::line_left(*width, pos.x, style) + offset.x) * canvas.scale;
+                for c in line.chars() {
+                    let (metrics, bitmap) = glyphs.entry(c).or_insert_with(|| raster.font.rasterize(c, pixel_size));
+                    let left = (pen + metrics.xmin as f32).round() as i64;
+                    let glyph_top = (baseline - (metrics.ymin + metrics.height as i32) as f32).round() as i64;
+                    canvas.blit_coverage(left, glyph_top, metrics.width, bitmap, color);
+                    pen += metrics.advance_width;
+                }
+            }
+        };
+        if let Some((offset, color)) = style.shadow {
+            draw_pass(self, offset, color);
+        }
+        if let Some((width, color)) = style.outline {
+            for i in 0..8 {
+                let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+                draw_pass(self, Vec2::new(angle.cos(), angle.sin()) * width, color);
+            }
+        }
+        draw_pass(self, Vec2::ZERO, style.color);
+    }
+
+    // 按字形的覆盖率位图混合颜色
+    fn blit_coverage(&mut self, left: i64, top: i64, width: usize, coverage: &[u8], color: Color) {
+        if width == 0 {
+            return;
+        }
+        for (row, line) in coverage.chunks(width).enumerate() {
+            let y = top + row as i64;
+            if y < 0 || y >= self.height as i64 {
+                continue;
+            }
+            for (column, &value) in line.iter().enumerate() {
+                let x = left + column as i64;
+                if x >= 0 && x < self.width as i64 && value > 0 {
+                    self.blend(x as usize, y as usize, color, value as f32 / 255.0);
+                }
+            }
+        }
+    }
+
+    /// 8位RGBA像素，从上到下逐行
+    pub fn to_rgba(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| pixel.map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8))
+            .collect()
+    }
+
+    /// 编码为PNG文件内容
+    pub fn encode_png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.to_rgba())
+    }
+}
+
+impl RenderBackend for Canvas {
+    /// 按层从下到上绘制命令列表
+    fn draw_list(&mut self, list: &DrawList) {
+        for item in list.sorted() {
+            self.blend = item.blend;
+            self.draw(&item.command);
+        }
+        self.blend = BlendMode::Alpha;
+    }
+}
+
+// 圆上第i个顶点的方向（与macroquad的draw_circle相同，从正右方开始）
+fn circle_point(i: usize) -> Vec2 {
+    let angle = i as f32 / CIRCLE_SIDES as f32 * std::f32::consts::TAU;
+    Vec2::new(angle.cos(), angle.sin())
+}
+
+/// 把8位RGBA像素编码为PNG：每行选择差值最小的滤波方式，用固定哈夫曼编码的deflate压缩重复字节
+pub fn encode_png(width: usize, height: usize, rgba: &[u8]) -> Vec<u8> {
+    let stride = width * 4;
+    let mut filtered = Vec::with_capacity((stride + 1) * height);
+    let mut previous = vec![0u8; stride];
+    for row in rgba.chunks(stride).take(height) {
+        // 0: 不滤波，1: 减去左边像素，2: 减去上一行
+        let candidates: [Vec<u8>; 3] = [
+            row.to_vec(),
+            (0..stride).map(|i| row[i].wrapping_sub(if i >= 4 { row[i - 4] } else { 0 })).collect(),
+            (0..stride).map(|i| row[i].wrapping_sub(previous[i])).collect(),
+        ];
+        let cost = |bytes: &Vec<u8>| bytes.iter().map(|&b| (b as i8).unsigned_abs() as u32).sum::<u32>();
+        let (filter, bytes) = candidates.iter().enumerate().min_by_key(|(_, bytes)| cost(bytes)).unwrap();
+        filtered.push(filter as u8);
+        filtered.extend_from_slice(bytes);
+        previous.copy_from_slice(row);
+    }
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = Vec::new();
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);  // 8位RGBA，不隔行
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_compress(&filtered));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+// deflate的位输出，低位在前
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // 哈夫曼码从最高位开始写
+    fn code(&mut self, code: u32, length: u32) {
+        self.bits(code.reverse_bits() >> (32 - length), length);
+    }
+
+    // 固定哈夫曼编码的字面量或长度符号
+    fn symbol(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+// deflate长度符号257-285对应的起始长度和附加位数
+const LENGTH_BASE: [u32; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+// zlib格式，只用距离为1的匹配压缩连续相同的字节（滤波后的平坦区域都是这样的字节串）
+fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter { bytes: vec![0x78, 0x01], buffer: 0, count: 0 };
+    writer.bits(1, 1);  // 最后一个块
+    writer.bits(1, 2);  // 固定哈夫曼编码
+    let mut i = 0;
+    while i < data.len() {
+        let run = if i > 0 {
+            data[i..].iter().take(258).take_while(|&&b| b == data[i - 1]).count()
+        } else {
+            0
+        };
+        if run >= 3 {
+            let index = LENGTH_BASE.iter().rposition(|&base| base <= run as u32).unwrap();
+            writer.symbol(257 + index as u32);
+            writer.bits(run as u32 - LENGTH_BASE[index], LENGTH_EXTRA[index]);
+            writer.code(0, 5);  // 距离1
+            i += run;
+        } else {
+            writer.symbol(data[i] as u32);
+            i += 1;
+        }
+    }
+    writer.symbol(256);
+    let mut out = writer.finish();
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
@@ -0,0 +1,227 @@
+// 绘制命令：与绘制后端无关的图元（矩形、三角形、四边形、线、圆、文字和方块）
+// 界面先生成命令列表，再交给后端执行：gpu 模块用macroquad绘制，raster 模块在CPU上光栅化（无窗口截图）
+// 每条命令属于一个绘制层，后端按层从下到上绘制，同一层内按加入的顺序
+// 方块的立体边缘和色盲图案的几何形状也在这里定义，两种后端画出的方块一致
+use macroquad::prelude::*;
+
+use crate::block::PALETTE_SIZE;
+use crate::text::TextStyle;
+use crate::theme::{Bevel, Theme};
+
+/// 绘制层，从下到上
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum Layer {
+    /// 背景、标题、网格底色、分数和候选区
+    #[default]
+    Background,
+    /// 网格线
+    Board,
+    /// 网格中、消除中、候选区和飞行中的方块
+    Blocks,
+    /// 消除闪白和粒子
+    Effects,
+    /// 拖拽时的放置预览
+    Preview,
+    /// 拖拽中的方块
+    Drag,
+    /// 飘字、闪屏、自动游玩提示和暂停按钮
+    Overlay,
+    /// 菜单、暂停、设置和游戏结束界面
+    Menu,
+}
+
+/// 混合方式
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BlendMode {
+    #[default]
+    Alpha,
+    /// 叠加混合：源颜色×源透明度加到目标颜色上，重叠处更亮
+    Additive,
+}
+
+/// 一个绘制图元，坐标为逻辑像素
+#[derive(Clone, Debug)]
+pub enum DrawCommand {
+    Rect { rect: Rect, color: Color },
+    /// 矩形边框，线条在矩形内侧，宽度为thickness的一半（与macroquad相同）
+    RectLines { rect: Rect, thickness: f32, color: Color },
+    Line { from: Vec2, to: Vec2, thickness: f32, color: Color },
+    Triangle { points: [Vec2; 3], color: Color },
+    /// 凸四边形，顶点按顺序排列（用于旋转的矩形）
+    Quad { points: [Vec2; 4], color: Color },
+    Circle { center: Vec2, radius: f32, color: Color },
+    CircleLines { center: Vec2, radius: f32, thickness: f32, color: Color },
+    /// 按样式在pos绘制文本，对齐方式见 TextStyle
+    Text { text: String, pos: Vec2, style: TextStyle },
+    /// 方块：index为调色板索引（贴图皮肤按它选择精灵），color为主题颜色（含淡出的透明度）
+    /// 没有贴图的后端按bevel画立体边缘，glyph为true时叠加色盲图案
+    Block { rect: Rect, index: u8, color: Color, bevel: Bevel, glyph: bool },
+}
+
+/// 命令列表中的一项
+#[derive(Clone, Debug)]
+pub struct DrawItem {
+    pub layer: Layer,
+    pub blend: BlendMode,
+    pub command: DrawCommand,
+}
+
+/// 一帧的绘制命令，新命令使用当前的绘制层和混合方式
+#[derive(Clone, Debug, Default)]
+pub struct DrawList {
+    pub items: Vec<DrawItem>,
+    layer: Layer,
+    blend: BlendMode,
+}
+
+impl DrawList {
+    pub fn new() -> Self {
+        DrawList::default()
+    }
+
+    /// 之后加入的命令画在layer层
+    pub fn set_layer(&mut self, layer: Layer) {
+        self.layer = layer;
+    }
+
+    /// 之后加入的命令使用blend混合
+    pub fn set_blend(&mut self, blend: BlendMode) {
+        self.blend = blend;
+    }
+
+    pub fn push(&mut self, command: DrawCommand) {
+        self.items.push(DrawItem { layer: self.layer, blend: self.blend, command });
+    }
+
+    /// 按绘制顺序排列的命令：层从下到上，同一层内按加入的顺序
+    pub fn sorted(&self) -> Vec<&DrawItem> {
+        let mut items: Vec<&DrawItem> = self.items.iter().collect();
+        items.sort_by_key(|item| item.layer);
+        items
+    }
+
+    /// 某一层的命令，按加入的顺序
+    pub fn layer_commands(&self, layer: Layer) -> impl Iterator<Item = &DrawCommand> {
+        self.items.iter().filter(move |item| item.layer == layer).map(|item| &item.command)
+    }
+
+    pub fn rect(&mut self, rect: Rect, color: Color) {
+        self.push(DrawCommand::Rect { rect, color });
+    }
+
+    pub fn rect_lines(&mut self, rect: Rect, thickness: f32, color: Color) {
+        self.push(DrawCommand::RectLines { rect, thickness, color });
+    }
+
+    pub fn line(&mut self, from: Vec2, to: Vec2, thickness: f32, color: Color) {
+        self.push(DrawCommand::Line { from, to, thickness, color });
+    }
+
+    pub fn triangle(&mut self, a: Vec2, b: Vec2, c: Vec2, color: Color) {
+        self.push(DrawCommand::Triangle { points: [a, b, c], color });
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Color) {
+        self.push(DrawCommand::Circle { center, radius, color });
+    }
+
+    pub fn text(&mut self, text: &str, pos: Vec2, style: TextStyle) {
+        self.push(DrawCommand::Text { text: text.to_string(), pos, style });
+    }
+
+    /// 按主题加入调色板索引为index的方块，alpha用于淡出
+    pub fn block(&mut self, rect: Rect, index: u8, alpha: f32, theme: &Theme) {
+        if rect.w <= 0.0 || alpha <= 0.0 {
+            return;
+        }
+        let base = theme.block_color(index);
+        let color = Color::new(base.r, base.g, base.b, base.a * alpha);
+        self.push(DrawCommand::Block { rect, index, color, bevel: theme.bevel, glyph: theme.glyphs });
+    }
+}
+
+/// 绘制后端：执行一帧的命令列表
+pub trait RenderBackend {
+    fn draw_list(&mut self, list: &DrawList);
+}
+
+/// 立体方块的五个四边形（顶点顺时针）和颜色：主体，上边、左边亮色，右边、下边暗色
+/// 边缘厚度为0时只有主体
+pub fn bevel_quads(rect: Rect, color: Color, bevel: &Bevel) -> Vec<([Vec2; 4], Color)> {
+    let (outer_tl, outer_tr) = (rect.point(), Vec2::new(rect.right(), rect.y));
+    let (outer_br, outer_bl) = (Vec2::new(rect.right(), rect.bottom()), Vec2::new(rect.x, rect.bottom()));
+    let mut quads = vec![([outer_tl, outer_tr, outer_br, outer_bl], color)];
+
+    let border = rect.w * bevel.border;
+    if border <= 0.0 {
+        return quads;
+    }
+    let light = Color::new(
+        (color.r + bevel.light).min(1.0),
+        (color.g + bevel.light).min(1.0),
+        (color.b + bevel.light).min(1.0),
+        color.a
+    );
+    let dark = Color::new(
+        (color.r - bevel.dark).max(0.0),
+        (color.g - bevel.dark).max(0.0),
+        (color.b - bevel.dark).max(0.0),
+        color.a
+    );
+    let inner_tl = outer_tl + Vec2::new(border, border);
+    let inner_tr = outer_tr + Vec2::new(-border, border);
+    let inner_br = outer_br + Vec2::new(-border, -border);
+    let inner_bl = outer_bl + Vec2::new(border, -border);
+    quads.push(([outer_tl, outer_tr, inner_tr, inner_tl], light));
+    quads.push(([outer_tl, inner_tl, inner_bl, outer_bl], light));
+    quads.push(([outer_tr, outer_br, inner_br, inner_tr], dark));
+    quads.push(([outer_bl, inner_bl, inner_br, outer_br], dark));
+    quads
+}
+
+/// 色盲模式下方块中间的图案：圆点、三角、菱形、十字、横条、叉、圆环，只看形状也能区分
+pub fn block_glyph(rect: Rect, color: Color, index: u8) -> Vec<DrawCommand> {
+    // 亮色方块上用深色图案，暗色方块上用浅色图案，透明度跟随方块
+    let luminance = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+    let ink = if luminance > 0.5 {
+        Color::new(0.0, 0.0, 0.0, 0.55 * color.a)
+    } else {
+        Color::new(1.0, 1.0, 1.0, 0.75 * color.a)
+    };
+    let center = rect.center();
+    let (cx, cy) = (center.x, center.y);
+    let r = rect.w * 0.22;
+    let thickness = (rect.w * 0.08).max(1.0);
+    let bar = |x: f32, y: f32, w: f32, h: f32| DrawCommand::Rect { rect: Rect::new(x, y, w, h), color: ink };
+    match index as usize % PALETTE_SIZE {
+        0 => vec![DrawCommand::Circle { center, radius: r * 0.8, color: ink }],
+        1 => vec![DrawCommand::Triangle {
+            points: [Vec2::new(cx, cy - r), Vec2::new(cx - r, cy + r * 0.8), Vec2::new(cx + r, cy + r * 0.8)],
+            color: ink,
+        }],
+        2 => {
+            let (right, bottom) = (Vec2::new(cx + r, cy), Vec2::new(cx, cy + r));
+            let (left, top) = (Vec2::new(cx - r, cy), Vec2::new(cx, cy - r));
+            vec![
+                DrawCommand::Triangle { points: [right, bottom, left], color: ink },
+                DrawCommand::Triangle { points: [left, top, right], color: ink },
+            ]
+        },
+        3 => vec![
+            bar(cx - r, cy - thickness / 2.0, r * 2.0, thickness),
+            bar(cx - thickness / 2.0, cy - r, thickness, r * 2.0),
+        ],
+        4 => [-r * 0.7, 0.0, r * 0.7]
+            .iter()
+            .map(|dy| bar(cx - r, cy + dy - thickness / 2.0, r * 2.0, thickness))
+            .collect(),
+        5 => {
+            let d = r * 0.8;
+            vec![
+                DrawCommand::Line { from: Vec2::new(cx - d, cy - d), to: Vec2::new(cx + d, cy + d), thickness, color: ink },
+                DrawCommand::Line { from: Vec2::new(cx - d, cy + d), to: Vec2::new(cx + d, cy - d), thickness, color: ink },
+            ]
+        },
+        _ => vec![DrawCommand::CircleLines { center, radius: r * 0.85, thickness, color: ink }],
+    }
+}
@@ -0,0 +1,114 @@
+// 回放：种子、生成配置和每一步的放置
+// 方块序列由种子决定，按步重放规则引擎即可还原任意一帧
+// 回放文件格式（键 = 值，# 开头为注释）：
+//   seed = 42
+//   config = 30,60,3          简单方块概率,标准方块概率,每次生成方块数
+//   moves = 0:3,4 1:0,0 ...   每步为 候选方块索引:x,y，以空格分隔
+use std::fmt::Write as _;
+
+use crate::bot::{Bot, BotStrength};
+use crate::engine::{GameConfig, GameEngine};
+
+/// 一步放置
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ReplayMove {
+    pub block_idx: usize,
+    pub grid_x: i32,
+    pub grid_y: i32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Replay {
+    pub config: GameConfig,
+    pub seed: u64,
+    pub moves: Vec<ReplayMove>,
+}
+
+impl Replay {
+    pub fn new(config: GameConfig, seed: u64) -> Self {
+        Replay { config, seed, moves: Vec::new() }
+    }
+
+    /// 由机器人从种子开始玩，最多max_moves步或到游戏结束
+    pub fn record(config: GameConfig, strength: BotStrength, seed: u64, max_moves: u32) -> Self {
+        let mut replay = Replay::new(config, seed);
+        let mut engine = GameEngine::new(config, seed);
+        let mut bot = Bot::for_game(strength, seed);
+        while engine.moves < max_moves {
+            let Some(m) = bot.choose_move(&engine.grid, &engine.current_blocks) else { break };
+            if engine.place(m.block_idx, m.grid_x, m.grid_y).is_none() {
+                break;
+            }
+            replay.moves.push(ReplayMove { block_idx: m.block_idx, grid_x: m.grid_x, grid_y: m.grid_y });
+        }
+        replay
+    }
+
+    /// 重放前frame步后的局面，frame为0时是开局，超过步数时是最后一帧
+    /// 某一步无法放置时返回错误（回放与当前规则不一致）
+    pub fn frame(&self, frame: usize) -> Result<GameEngine, String> {
+        let mut engine = GameEngine::new(self.config, self.seed);
+        for (i, m) in self.moves.iter().take(frame).enumerate() {
+            if engine.place(m.block_idx, m.grid_x, m.grid_y).is_none() {
+                return Err(format!("第{}步无法放置: {}:{},{}", i + 1, m.block_idx, m.grid_x, m.grid_y));
+            }
+        }
+        Ok(engine)
+    }
+
+    /// 解析回放文件
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut seed = None;
+        let mut config = GameConfig::default();
+        let mut moves = Vec::new();
+        let lines = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("无法识别的行: {}", line));
+            };
+            let value = value.trim();
+            match key.trim() {
+                "seed" => seed = Some(value.parse().map_err(|_| format!("无效的种子: {}", value))?),
+                "config" => config = GameConfig::parse(value)?,
+                // moves 可以分成多行
+                "moves" => {
+                    for token in value.split_whitespace() {
+                        moves.push(parse_move(token).ok_or(format!("无效的一步: {}", token))?);
+                    }
+                },
+                other => return Err(format!("未知的键: {}", other)),
+            }
+        }
+        let seed = seed.ok_or("缺少 seed")?;
+        Ok(Replay { config, seed, moves })
+    }
+
+    /// 写成回放文件
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "seed = {}", self.seed);
+        let _ = writeln!(out, "config = {}", self.config.to_text());
+        // 每行16步
+        for chunk in self.moves.chunks(16) {
+            let moves: Vec<String> = chunk.iter()
+                .map(|m| format!("{}:{},{}", m.block_idx, m.grid_x, m.grid_y))
+                .collect();
+            let _ = writeln!(out, "moves = {}", moves.join(" "));
+        }
+        out
+    }
+}
+
+// "索引:x,y"
+fn parse_move(token: &str) -> Option<ReplayMove> {
+    let (block_idx, position) = token.split_once(':')?;
+    let (grid_x, grid_y) = position.split_once(',')?;
+    Some(ReplayMove {
+        block_idx: block_idx.parse().ok()?,
+        grid_x: grid_x.parse().ok()?,
+        grid_y: grid_y.parse().ok()?,
+    })
+}
@@ -1,26 +1,94 @@
 // 保存和加载游戏数据
-use macroquad::prelude::*;
+// 原生平台保存在用户目录下的 .block_blast_save 文件，网页版保存在 localStorage（由 block_blast_bridge.js 提供）
+// 存档是逐行的 键=值 文本，读不到或无法解析时使用默认值
+use crate::settings::Settings;
 
 pub struct SaveData {
     pub high_score: u32,
+    pub settings: Settings,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SaveData {
     pub fn new() -> Self {
         SaveData {
             high_score: 0,
+            settings: Settings::default(),
         }
     }
-    
+
     pub fn save(&self) {
-        // 简化版本：我们暂时不实际保存
-        // 由于WASM环境中存储方式可能不同，这里只是定义接口
-        println!("保存最高分: {}", self.high_score);
+        if !write_storage(&self.to_text()) {
+            println!("无法保存游戏数据");
+        }
     }
-    
+
     pub fn load() -> Self {
-        // 简化版本：我们暂时总是返回默认数据
-        // 实际应用中，可以从localStorage (Web)或本地文件系统加载
-        SaveData::new()
+        read_storage().map(|text| SaveData::from_text(&text)).unwrap_or_default()
     }
-} 
\ No newline at end of file
+
+    fn to_text(&self) -> String {
+        let mut out = format!("high_score={}\n", self.high_score);
+        self.settings.write_fields(&mut out);
+        out
+    }
+
+    fn from_text(text: &str) -> Self {
+        let mut data = SaveData::new();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "high_score" {
+                data.high_score = value.parse().unwrap_or(0);
+            } else {
+                data.settings.read_field(key, value);
+            }
+        }
+        data
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path() -> std::path::PathBuf {
+    std::env::var_os("APPDATA")
+        .or_else(|| std::env::var_os("HOME"))
+        .map(std::path::PathBuf::from)
+        .unwrap_or_default()
+        .join(".block_blast_save")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_storage() -> Option<String> {
+    std::fs::read_to_string(save_path()).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_storage(text: &str) -> bool {
+    std::fs::write(save_path(), text).is_ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+    // 把存档复制到缓冲区，返回字节数；没有存档或缓冲区不够时返回-1
+    fn bb_storage_load(ptr: *mut u8, max_len: i32) -> i32;
+    fn bb_storage_save(ptr: *const u8, len: i32);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_storage() -> Option<String> {
+    let mut buffer = vec![0u8; 4096];
+    let len = unsafe { bb_storage_load(buffer.as_mut_ptr(), buffer.len() as i32) };
+    buffer.truncate(usize::try_from(len).ok()?);
+    String::from_utf8(buffer).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_storage(text: &str) -> bool {
+    unsafe { bb_storage_save(text.as_ptr(), text.len() as i32) };
+    true
+}
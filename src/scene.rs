@@ -0,0 +1,63 @@
+// 对局界面的绘制命令：对局画面（main.rs 的 draw_game）和无窗口截图（snapshot）共用同一套布局
+use macroquad::prelude::*;
+
+use crate::block::BlockShape;
+use crate::layout::Layout;
+use crate::locale::{tr, tr_args};
+use crate::render::{DrawList, Layer};
+use crate::text::{HAlign, TextStyle};
+use crate::theme::Theme;
+
+/// 背景层：背景、标题、网格底色和边框、分数、分隔线和候选区
+/// board为网格左上角（震屏时带偏移），分数仍与未偏移的网格对齐
+pub fn draw_hud(list: &mut DrawList, layout: &Layout, theme: &Theme, ui_scale: f32, board: Vec2, score: u32, high_score: u32) {
+    list.set_layer(Layer::Background);
+    list.rect(Rect::new(0.0, 0.0, layout.screen.x, layout.screen.y), theme.background);
+
+    // 标题，字体大小乘以界面缩放
+    let header = layout.header;
+    list.text(
+        &tr("app.title"),
+        header.center(),
+        TextStyle::new(20.0 * ui_scale, theme.text).shadow(Vec2::new(0.0, 2.0), Color::new(0.0, 0.0, 0.0, 0.5))
+    );
+
+    // 网格底色和边框，边框宽度随界面缩放
+    let grid = layout.grid;
+    let backdrop = Rect::new(board.x - 5.0, board.y - 5.0, grid.w + 10.0, grid.h + 10.0);
+    list.rect(backdrop, theme.grid_background);
+    list.rect_lines(backdrop, 2.0 * ui_scale, theme.grid_border);
+
+    // 分数与网格左边对齐，最高分与网格右边对齐
+    list.text(
+        &tr_args("hud.score", &[("score", &score)]),
+        Vec2::new(grid.x, layout.score_y),
+        TextStyle::new(15.0 * ui_scale, theme.text).align(HAlign::Left)
+    );
+    list.text(
+        &tr_args("hud.high_score", &[("score", &high_score)]),
+        Vec2::new(grid.right(), layout.score_y),
+        TextStyle::new(15.0 * ui_scale, theme.text).align(HAlign::Right)
+    );
+
+    let (separator_start, separator_end) = layout.separator;
+    list.line(separator_start, separator_end, 2.0 * ui_scale, theme.separator);
+
+    // 候选区背景和标题
+    let tray = layout.tray;
+    list.rect(tray, theme.tray_background);
+    list.text(
+        &tr("hud.tray"),
+        Vec2::new(tray.center().x, layout.tray_title_y),
+        TextStyle::new(20.0 * ui_scale, theme.text)
+    );
+}
+
+/// 以锚点（(0,0)格子的中心）和格子大小在当前层加入方块的每一格
+pub fn draw_block_cells(list: &mut DrawList, block: &BlockShape, anchor: Vec2, cell: f32, theme: &Theme) {
+    for &(dx, dy) in &block.cells {
+        let center = anchor + Vec2::new(dx as f32, dy as f32) * cell;
+        let rect = Rect::new(center.x - cell / 2.0, center.y - cell / 2.0, cell, cell);
+        list.block(rect, block.color, 1.0, theme);
+    }
+}
@@ -0,0 +1,88 @@
+// 玩家设置，随存档一起保存
+// 存档格式为逐行的 键=值，新增设置时在 write_fields / read_field 中各加一行
+use crate::locale::Locale;
+use crate::skin;
+use crate::theme;
+
+/// 玩家设置
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub music_volume: f32,  // 背景音乐音量，0.0-1.0
+    pub sfx_volume: f32,    // 音效音量，0.0-1.0
+    pub music_muted: bool,
+    pub sfx_muted: bool,
+    pub reduce_motion: bool,  // 关闭震屏、闪屏等动态效果
+    pub language: Locale,
+    pub ui_scale: f32,        // 界面缩放，取值见 display::UI_SCALES
+    pub theme: String,        // 主题名称，自定义主题加载失败时退回默认主题
+    pub colorblind: bool,     // 色盲模式：色盲安全的调色板和方块图案
+    pub skin: String,         // 方块皮肤名称，皮肤包加载失败时退回默认皮肤
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            music_volume: 0.6,
+            sfx_volume: 1.0,
+            music_muted: false,
+            sfx_muted: false,
+            reduce_motion: false,
+            language: Locale::ZhCn,
+            ui_scale: 1.0,
+            theme: theme::DEFAULT_THEME.to_string(),
+            colorblind: false,
+            skin: skin::DEFAULT_SKIN.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// 实际使用的背景音乐音量（静音时为0）
+    pub fn effective_music_volume(&self) -> f32 {
+        if self.music_muted { 0.0 } else { self.music_volume }
+    }
+
+    /// 实际使用的音效音量（静音时为0）
+    pub fn effective_sfx_volume(&self) -> f32 {
+        if self.sfx_muted { 0.0 } else { self.sfx_volume }
+    }
+
+    /// 把设置写成 键=值 行
+    pub fn write_fields(&self, out: &mut String) {
+        out.push_str(&format!("music_volume={}\n", self.music_volume));
+        out.push_str(&format!("sfx_volume={}\n", self.sfx_volume));
+        out.push_str(&format!("music_muted={}\n", self.music_muted));
+        out.push_str(&format!("sfx_muted={}\n", self.sfx_muted));
+        out.push_str(&format!("reduce_motion={}\n", self.reduce_motion));
+        out.push_str(&format!("language={}\n", self.language.code()));
+        out.push_str(&format!("ui_scale={}\n", self.ui_scale));
+        out.push_str(&format!("theme={}\n", self.theme));
+        out.push_str(&format!("colorblind={}\n", self.colorblind));
+        out.push_str(&format!("skin={}\n", self.skin));
+    }
+
+    /// 读取一个字段，不认识的键或无效的值返回false，保持原值
+    pub fn read_field(&mut self, key: &str, value: &str) -> bool {
+        let volume = || value.parse::<f32>().ok().filter(|v| (0.0..=1.0).contains(v));
+        let flag = || value.parse::<bool>().ok();
+        match key {
+            "music_volume" => volume().map(|v| self.music_volume = v).is_some(),
+            "sfx_volume" => volume().map(|v| self.sfx_volume = v).is_some(),
+            "music_muted" => flag().map(|v| self.music_muted = v).is_some(),
+            "sfx_muted" => flag().map(|v| self.sfx_muted = v).is_some(),
+            "reduce_motion" => flag().map(|v| self.reduce_motion = v).is_some(),
+            "colorblind" => flag().map(|v| self.colorblind = v).is_some(),
+            "language" => Locale::from_code(value).map(|v| self.language = v).is_some(),
+            "theme" if !value.is_empty() => {
+                self.theme = value.to_string();
+                true
+            },
+            "skin" if !value.is_empty() => {
+                self.skin = value.to_string();
+                true
+            },
+            "ui_scale" => value.parse::<f32>().ok().filter(|v| (0.5..=2.0).contains(v)).map(|v| self.ui_scale = v).is_some(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// 批量模拟：由机器人在无界面环境下连续游玩，统计难度参数对游戏的影响
+use crate::block::SHAPE_NAMES;
+use crate::bot::{Bot, BotStrength};
+use crate::engine::{GameConfig, GameEngine};
+
+/// 一局结束的原因
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameOverCause {
+    NoFit,      // 所有候选方块都无处可放
+    MoveLimit,  // 达到步数上限，强制结束
+}
+
+impl GameOverCause {
+    pub fn name(self) -> &'static str {
+        match self {
+            GameOverCause::NoFit => "no_fit",
+            GameOverCause::MoveLimit => "move_limit",
+        }
+    }
+}
+
+/// 单局结果
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub score: u32,
+    pub moves: u32,
+    pub lines_cleared: u32,
+    pub max_combo: u32,
+    pub cause: GameOverCause,
+    pub killer_pieces: Vec<&'static str>,  // 游戏结束时放不下的方块形状
+}
+
+/// 用指定种子和机器人强度玩一局
+/// 方块序列和机器人的随机选择都由种子决定，结果可复现
+pub fn play_game(config: GameConfig, strength: BotStrength, seed: u64, max_moves: u32) -> GameRecord {
+    let mut engine = GameEngine::new(config, seed);
+    let mut bot = Bot::for_game(strength, seed);
+    let mut lines_cleared = 0;
+    let mut max_combo = 0;
+
+    let cause = loop {
+        if engine.moves >= max_moves {
+            break GameOverCause::MoveLimit;
+        }
+        let Some(m) = bot.choose_move(&engine.grid, &engine.current_blocks) else {
+            break GameOverCause::NoFit;
+        };
+        if let Some(outcome) = engine.place(m.block_idx, m.grid_x, m.grid_y) {
+            lines_cleared += outcome.lines_cleared;
+            max_combo = max_combo.max(outcome.combo);
+        }
+    };
+
+    let killer_pieces = match cause {
+        GameOverCause::NoFit => engine.current_blocks.iter().map(|b| b.shape_name()).collect(),
+        GameOverCause::MoveLimit => Vec::new(),
+    };
+
+    GameRecord {
+        seed,
+        score: engine.score,
+        moves: engine.moves,
+        lines_cleared,
+        max_combo,
+        cause,
+        killer_pieces,
+    }
+}
+
+/// 一组配置的统计汇总
+pub struct Summary {
+    pub config: GameConfig,
+    pub strength: BotStrength,
+    pub scores: Vec<u32>,                       // 升序排列的所有得分
+    pub mean_score: f32,
+    pub mean_moves: f32,
+    pub no_fit: u32,
+    pub move_limit: u32,
+    pub killer_pieces: Vec<(&'static str, u32)>, // 按SHAPE_NAMES顺序的计数
+}
+
+impl Summary {
+    pub fn from_records(config: GameConfig, strength: BotStrength, records: &[GameRecord]) -> Self {
+        let games = records.len().max(1) as f32;
+        let mut scores: Vec<u32> = records.iter().map(|r| r.score).collect();
+        scores.sort_unstable();
+
+        let mut killer_pieces: Vec<(&'static str, u32)> = SHAPE_NAMES.iter().map(|&name| (name, 0)).collect();
+        for name in records.iter().flat_map(|r| &r.killer_pieces) {
+            if let Some(entry) = killer_pieces.iter_mut().find(|(n, _)| n == name) {
+                entry.1 += 1;
+            }
+        }
+
+        Summary {
+            config,
+            strength,
+            mean_score: records.iter().map(|r| r.score as f32).sum::<f32>() / games,
+            mean_moves: records.iter().map(|r| r.moves as f32).sum::<f32>() / games,
+            no_fit: records.iter().filter(|r| r.cause == GameOverCause::NoFit).count() as u32,
+            move_limit: records.iter().filter(|r| r.cause == GameOverCause::MoveLimit).count() as u32,
+            scores,
+            killer_pieces,
+        }
+    }
+
+    /// 得分百分位数（p取0-100，最近秩法）
+    pub fn percentile(&self, p: u32) -> u32 {
+        if self.scores.is_empty() {
+            return 0;
+        }
+        let rank = (p.min(100) as usize * self.scores.len()).div_ceil(100).max(1);
+        self.scores[rank - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_seed_gives_fixed_result() {
+        // 规则、方块生成或机器人改变时这里的结果会变化，需要确认后再更新
+        let record = play_game(GameConfig::default(), BotStrength::Greedy, 2024, 150);
+        assert_eq!((record.score, record.moves, record.lines_cleared, record.max_combo), (1000, 26, 7, 3));
+        assert_eq!(record.cause, GameOverCause::NoFit);
+        assert_eq!(record.killer_pieces, vec!["I"]);
+
+        let again = play_game(GameConfig::default(), BotStrength::Greedy, 2024, 150);
+        assert_eq!((again.score, again.moves, again.killer_pieces), (record.score, record.moves, record.killer_pieces));
+    }
+
+    #[test]
+    fn move_limit_ends_the_game() {
+        let record = play_game(GameConfig::default(), BotStrength::Greedy, 2024, 10);
+        assert_eq!((record.moves, record.cause), (10, GameOverCause::MoveLimit));
+        assert!(record.killer_pieces.is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+// 方块皮肤：方块的绘制方式
+// 内置两种皮肤：bevel（程序生成的立体边缘，不需要贴图，也是贴图无法加载时的后备）和 glossy（启动时生成的光泽贴图）
+// 皮肤包位于 resources/skins/<名称>/，由 resources/skins/index.txt 逐行列出，每个包包含 skin.txt 和图集图片
+// skin.txt 的格式（键 = 值）：
+//   name = 显示名称（默认为目录名）
+//   atlas = 图集文件名（默认为 atlas.png）
+//   sprite_size = 每个精灵的边长（像素），精灵从左到右、从上到下按调色板索引排列
+//   tint = true 时图集只有一个白色精灵，按主题调色板着色；false 时每种颜色一个精灵，不受主题调色板影响
+//   filter = nearest 或 linear（默认），像素风格的皮肤使用 nearest
+// 绘制由 gpu 模块完成：命令列表中连续的方块合成一个网格一次绘制，其他命令和混合方式的切换会打断合并（见 gpu.rs 的文件头）
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+
+use macroquad::prelude::*;
+
+use crate::block::PALETTE_SIZE;
+
+/// 默认皮肤的名称
+pub const DEFAULT_SKIN: &str = "bevel";
+
+const SKIN_DIR: &str = "resources/skins";
+
+/// 方块的绘制方式
+#[derive(Clone, Debug)]
+pub enum SkinStyle {
+    /// 程序生成的立体边缘，参数来自主题
+    Bevel,
+    /// 图集中的精灵
+    Atlas {
+        texture: Texture2D,
+        sprite_size: f32,
+        tint: bool,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Skin {
+    pub name: String,
+    pub style: SkinStyle,
+}
+
+impl Skin {
+    /// 是否为内置皮肤（显示名称需要翻译）
+    pub fn is_builtin(&self) -> bool {
+        self.name == DEFAULT_SKIN || self.name == "glossy"
+    }
+
+    /// 调色板索引对应的精灵在图集中的uv范围（左上角和大小）
+    pub fn sprite_uv(texture: &Texture2D, sprite_size: f32, sprite: usize) -> (Vec2, Vec2) {
+        let size = texture.size();
+        let columns = ((size.x / sprite_size) as usize).max(1);
+        let corner = Vec2::new((sprite % columns) as f32, (sprite / columns) as f32) * sprite_size;
+        (corner / size, Vec2::splat(sprite_size) / size)
+    }
+}
+
+// 可选的皮肤，默认皮肤在最前面，glossy和皮肤包由 load_skins 加入
+static SKINS: Lazy<Mutex<Vec<Arc<Skin>>>> = Lazy::new(|| {
+    Mutex::new(vec![Arc::new(Skin { name: DEFAULT_SKIN.to_string(), style: SkinStyle::Bevel })])
+});
+
+static CURRENT: Lazy<Mutex<Arc<Skin>>> = Lazy::new(|| Mutex::new(SKINS.lock().unwrap()[0].clone()));
+
+/// 当前皮肤
+pub fn current() -> Arc<Skin> {
+    CURRENT.lock().unwrap().clone()
+}
+
+/// 所有可选皮肤的名称，按设置界面中切换的顺序
+pub fn names() -> Vec<String> {
+    SKINS.lock().unwrap().iter().map(|skin| skin.name.clone()).collect()
+}
+
+/// 切换到指定名称的皮肤，找不到时返回false并保持当前皮肤
+pub fn set_skin(name: &str) -> bool {
+    let skin = SKINS.lock().unwrap().iter().find(|skin| skin.name == name).cloned();
+    let Some(skin) = skin else { return false };
+    *CURRENT.lock().unwrap() = skin;
+    true
+}
+
+fn register(skin: Skin) {
+    let mut skins = SKINS.lock().unwrap();
+    match skins.iter().position(|s| s.name == skin.name) {
+        Some(i) => skins[i] = Arc::new(skin),
+        None => skins.push(Arc::new(skin)),
+    }
+}
+
+// 光泽皮肤的白色精灵：圆角、上亮下暗的渐变、顶部高光和深色描边，按调色板着色
+fn glossy_sprite() -> Image {
+    const SIZE: u16 = 64;
+    let mut image = Image::gen_image_color(SIZE, SIZE, Color::new(0.0, 0.0, 0.0, 0.0));
+    let size = SIZE as f32;
+    let radius = size * 0.16;
+    for py in 0..SIZE as u32 {
+        for px in 0..SIZE as u32 {
+            let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+            // 到圆角矩形边缘的距离（内部为负）
+            let dx = (radius - x).max(x - (size - radius)).max(0.0);
+            let dy = (radius - y).max(y - (size - radius)).max(0.0);
+            let distance = (dx * dx + dy * dy).sqrt() - radius;
+            let alpha = (0.5 - distance).clamp(0.0, 1.0);
+            if alpha <= 0.0 {
+                continue;
+            }
+            let t = y / size;
+            let mut value = 1.0 - 0.3 * t;
+            // 上半部分的高光
+            if t < 0.45 && x > size * 0.12 && x < size * 0.88 {
+                value += 0.25 * (1.0 - t / 0.45);
+            }
+            // 描边
+            if distance > -size * 0.05 {
+                value *= 0.6;
+            }
+            let value = value.min(1.0);
+            image.set_pixel(px, py, Color::new(value, value, value, alpha));
+        }
+    }
+    image
+}
+
+// 解析皮肤包的 skin.txt，返回 (名称, 图集文件, 精灵边长, 是否着色, 过滤方式)
+fn parse_pack(dir: &str, source: &str) -> Result<(String, String, f32, bool, FilterMode), String> {
+    let mut name = dir.to_string();
+    let mut atlas = "atlas.png".to_string();
+    let mut sprite_size = None;
+    let mut tint = false;
+    let mut filter = FilterMode::Linear;
+    let lines = source
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    for line in lines {
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("无法识别的行: {}", line));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let valid = match key {
+            "name" if !value.is_empty() => {
+                name = value.to_string();
+                true
+            },
+            "atlas" if !value.is_empty() => {
+                atlas = value.to_string();
+                true
+            },
+            "sprite_size" => value.parse::<f32>().ok().filter(|&s| s >= 1.0).map(|s| sprite_size = Some(s)).is_some(),
+            "tint" => value.parse::<bool>().map(|v| tint = v).is_ok(),
+            "filter" => match value {
+                "nearest" => {
+                    filter = FilterMode::Nearest;
+                    true
+                },
+                "linear" => {
+                    filter = FilterMode::Linear;
+                    true
+                },
+                _ => false,
+            },
+            _ => false,
+        };
+        if !valid {
+            return Err(format!("无效的设置: {} = {}", key, value));
+        }
+    }
+    let sprite_size = sprite_size.ok_or("缺少 sprite_size")?;
+    Ok((name, atlas, sprite_size, tint, filter))
+}
+
+async fn load_pack(dir: &str) -> Result<Skin, String> {
+    let source = load_string(&format!("{}/{}/skin.txt", SKIN_DIR, dir)).await.map_err(|err| err.to_string())?;
+    let (name, atlas, sprite_size, tint, filter) = parse_pack(dir, &source)?;
+    let texture = load_texture(&format!("{}/{}/{}", SKIN_DIR, dir, atlas)).await.map_err(|err| err.to_string())?;
+    texture.set_filter(filter);
+    // 不着色时每种颜色都需要一个精灵
+    let size = texture.size();
+    let capacity = ((size.x / sprite_size) as usize) * ((size.y / sprite_size) as usize);
+    let needed = if tint { 1 } else { PALETTE_SIZE };
+    if capacity < needed {
+        return Err(format!("图集 {} 只有 {} 个精灵，需要 {} 个", atlas, capacity, needed));
+    }
+    Ok(Skin { name, style: SkinStyle::Atlas { texture, sprite_size, tint } })
+}
+
+/// 生成内置的光泽皮肤并加载 index.txt 中列出的皮肤包，需要在窗口创建后调用
+/// 加载失败的皮肤包打印原因后跳过
+pub async fn load_skins() {
+    let glossy = Texture2D::from_image(&glossy_sprite());
+    register(Skin {
+        name: "glossy".to_string(),
+        style: SkinStyle::Atlas { texture: glossy, sprite_size: 64.0, tint: true },
+    });
+
+    let Ok(index) = load_string(&format!("{}/index.txt", SKIN_DIR)).await else {
+        println!("没有皮肤包列表 {}/index.txt", SKIN_DIR);
+        return;
+    };
+    let dirs = index
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    for dir in dirs {
+        match load_pack(dir).await {
+            Ok(skin) => {
+                println!("已加载皮肤包: {}", skin.name);
+                register(skin);
+            },
+            Err(err) => println!("无法加载皮肤包 {}: {}", dir, err),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// 棋盘快照：网格、候选方块和分数，用于无窗口截图和问题报告
+// 快照可以来自规则引擎（例如回放的某一帧），也可以用一行棋盘记谱写出：
+//   <8行网格，以 / 分隔> [候选方块] [分数]
+//   网格每行8个字符，. 为空格子，0-6 为该格方块的调色板索引
+//   候选方块以逗号分隔，每个写作 形状名称:调色板索引（形状名称见 block::SHAPE_NAMES），没有候选方块时写 -
+// 例如 "......../......../......../......../......../......../00000000/1111111. L:2,O:0,cross:5 1200"
+// draw_list 用与对局界面相同的绘制代码生成命令（不含拖拽、动画和特效）
+use macroquad::prelude::*;
+
+use crate::block::{BlockShape, PALETTE_SIZE};
+use crate::engine::{GameConfig, GameEngine};
+use crate::grid::Grid;
+use crate::layout::Layout;
+use crate::raster::Canvas;
+use crate::render::{DrawList, Layer, RenderBackend};
+use crate::scene;
+use crate::theme::Theme;
+
+#[derive(Clone)]
+pub struct Snapshot {
+    pub grid: Grid,
+    pub tray: Vec<BlockShape>,
+    pub score: u32,
+    pub high_score: u32,
+    pub blocks_per_generation: usize,  // 决定候选方块的显示大小
+}
+
+impl Snapshot {
+    pub fn from_engine(engine: &GameEngine) -> Self {
+        Snapshot {
+            grid: engine.grid.clone(),
+            tray: engine.current_blocks.clone(),
+            score: engine.score,
+            high_score: engine.score,
+            blocks_per_generation: engine.config.blocks_per_generation,
+        }
+    }
+
+    /// 解析棋盘记谱，最高分与分数相同，每次生成的方块数按默认配置（候选方块更多时按候选方块数）
+    pub fn parse(notation: &str) -> Result<Self, String> {
+        let mut sections = notation.split_whitespace();
+        let board = sections.next().ok_or("棋盘记谱为空")?;
+        let rows: Vec<&str> = board.split('/').collect();
+        if rows.len() != 8 {
+            return Err(format!("网格应为8行，实际为{}行", rows.len()));
+        }
+        let mut grid = Grid::new();
+        for (y, row) in rows.iter().enumerate() {
+            let cells: Vec<char> = row.chars().collect();
+            if cells.len() != 8 {
+                return Err(format!("第{}行应为8个字符: {}", y + 1, row));
+            }
+            for (x, c) in cells.into_iter().enumerate() {
+                grid.cells[y][x] = match c {
+                    '.' => None,
+                    _ => match c.to_digit(10) {
+                        Some(index) if (index as usize) < PALETTE_SIZE => Some(index as u8),
+                        _ => return Err(format!("第{}行有无效的格子: {}", y + 1, c)),
+                    },
+                };
+            }
+        }
+
+        let tray = match sections.next() {
+            None | Some("-") => Vec::new(),
+            Some(tray) => tray.split(',').map(parse_block).collect::<Result<_, _>>()?,
+        };
+        let score = match sections.next() {
+            Some(score) => score.parse().map_err(|_| format!("无效的分数: {}", score))?,
+            None => 0,
+        };
+        if let Some(extra) = sections.next() {
+            return Err(format!("棋盘记谱中多余的内容: {}", extra));
+        }
+        let blocks_per_generation = tray.len().max(GameConfig::default().blocks_per_generation);
+        Ok(Snapshot { grid, tray, score, high_score: score, blocks_per_generation })
+    }
+
+    /// 写成棋盘记谱，不是预定义形状的候选方块无法写出，记为 custom
+    pub fn notation(&self) -> String {
+        let rows: Vec<String> = self.grid.cells.iter()
+            .map(|row| row.iter().map(|cell| cell.map_or('.', |index| char::from(b'0' + index))).collect())
+            .collect();
+        let tray = if self.tray.is_empty() {
+            "-".to_string()
+        } else {
+            let blocks: Vec<String> = self.tray.iter()
+                .map(|block| format!("{}:{}", block.shape_name(), block.color))
+                .collect();
+            blocks.join(",")
+        };
+        format!("{} {} {}", rows.join("/"), tray, self.score)
+    }
+
+    /// 在CPU上渲染逻辑大小为screen、像素比为scale的截图
+    /// 没有窗口时macroquad字体不可用，界面文字按 locale 的规则退回英文
+    pub fn render(&self, screen: Vec2, scale: f32, theme: &Theme) -> Canvas {
+        let layout = Layout::new(screen, self.blocks_per_generation);
+        let mut canvas = Canvas::new(screen, scale);
+        canvas.draw_list(&self.draw_list(&layout, theme, 1.0));
+        canvas
+    }
+
+    /// 按布局和主题生成对局界面的绘制命令：标题、网格、分数、候选区和候选方块
+    pub fn draw_list(&self, layout: &Layout, theme: &Theme, ui_scale: f32) -> DrawList {
+        let mut list = DrawList::new();
+        let board = layout.grid.point();
+        scene::draw_hud(&mut list, layout, theme, ui_scale, board, self.score, self.high_score);
+        self.grid.draw(&mut list, board, layout.cell_size, theme);
+
+        // 候选方块以 (0,0) 格子的中心放在槽位中心，格子大小为槽位的1/5，与对局界面相同
+        list.set_layer(Layer::Blocks);
+        for (idx, block) in self.tray.iter().enumerate() {
+            let (anchor, block_size) = layout.tray_slot(idx, self.tray.len());
+            scene::draw_block_cells(&mut list, block, anchor, block_size / 5.0, theme);
+        }
+        list
+    }
+}
+
+// "形状名称:调色板索引"
+fn parse_block(text: &str) -> Result<BlockShape, String> {
+    let (name, color) = text.split_once(':').ok_or(format!("候选方块应写作 形状:颜色: {}", text))?;
+    let color = color.parse::<u8>().ok()
+        .filter(|&c| (c as usize) < PALETTE_SIZE)
+        .ok_or(format!("无效的方块颜色: {}", text))?;
+    BlockShape::from_name(name, color).ok_or(format!("未知的方块形状: {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::theme;
+
+    // 画布上逻辑坐标处的8位RGBA像素
+    fn pixel(canvas: &Canvas, pos: Vec2) -> [u8; 4] {
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        let offset = (y * canvas.width() + x) * 4;
+        canvas.to_rgba()[offset..offset + 4].try_into().unwrap()
+    }
+
+    fn rgba(color: Color) -> [u8; 4] {
+        [color.r, color.g, color.b, color.a].map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    #[test]
+    fn render_fills_cells_with_palette_colors() {
+        let snapshot = Snapshot::parse("3......./......../......../......../......../......../......../0000000. O:4 10").unwrap();
+        let screen = Vec2::new(400.0, 600.0);
+        let theme = theme::current();
+        let canvas = snapshot.render(screen, 1.0, &theme);
+        let layout = Layout::new(screen, snapshot.blocks_per_generation);
+        let center = |x: usize, y: usize| {
+            layout.grid.point() + (Vec2::new(x as f32, y as f32) + 0.5) * layout.cell_size
+        };
+
+        assert_eq!(pixel(&canvas, center(0, 0)), rgba(theme.block_color(3)));
+        assert_eq!(pixel(&canvas, center(0, 7)), rgba(theme.block_color(0)));
+        assert_eq!(pixel(&canvas, center(6, 7)), rgba(theme.block_color(0)));
+        // 空格子显示网格背景，与其他空格子相同
+        let empty = pixel(&canvas, center(7, 7));
+        assert_ne!(empty, rgba(theme.block_color(0)));
+        assert_eq!(empty, pixel(&canvas, center(4, 3)));
+        // 候选方块 (0,0) 格子的中心在槽位中心
+        let (anchor, _) = layout.tray_slot(0, snapshot.tray.len());
+        assert_eq!(pixel(&canvas, anchor), rgba(theme.block_color(4)));
+    }
+}
@@ -0,0 +1,215 @@
+// 程序化音效合成：启动时把简单音色和背景音乐合成为PCM采样，再编码成WAV交给音频后端
+// 不需要打包音频文件，可以减小WASM下载体积
+// 全部是纯函数，不依赖音频设备，可以在无界面环境下直接检查生成的采样
+use std::f32::consts::PI;
+
+/// 合成使用的采样率，与原生音频后端的混音采样率一致，播放时无需重采样
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// 基础波形
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+}
+
+impl Waveform {
+    // phase 为 [0, 1) 内的相位
+    fn sample(self, phase: f32) -> f32 {
+        match self {
+            Waveform::Sine => (phase * 2.0 * PI).sin(),
+            Waveform::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+        }
+    }
+}
+
+/// 时长对应的采样数
+pub fn sample_count(duration: f32) -> usize {
+    (duration.max(0.0) * SAMPLE_RATE as f32) as usize
+}
+
+/// 相对base升高semitones个半音后的频率
+pub fn semitone(base: f32, semitones: i32) -> f32 {
+    base * 2f32.powf(semitones as f32 / 12.0)
+}
+
+/// 单个音：频率从freq_start线性滑到freq_end，5毫秒起音后按平方曲线衰减到0
+pub fn tone(waveform: Waveform, freq_start: f32, freq_end: f32, duration: f32, volume: f32) -> Vec<f32> {
+    let count = sample_count(duration);
+    let attack = sample_count(0.005).max(1);
+    let mut phase = 0.0f32;
+    let mut samples = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let progress = i as f32 / count as f32;
+        let freq = freq_start + (freq_end - freq_start) * progress;
+        let attack_gain = (i as f32 / attack as f32).min(1.0);
+        let decay_gain = (1.0 - progress) * (1.0 - progress);
+
+        samples.push(waveform.sample(phase) * volume * attack_gain * decay_gain);
+        phase = (phase + freq / SAMPLE_RATE as f32).fract();
+    }
+    samples
+}
+
+/// 把source按gain叠加到target上，target不够长时补齐
+pub fn mix_into(target: &mut Vec<f32>, source: &[f32], offset: usize, gain: f32) {
+    if target.len() < offset + source.len() {
+        target.resize(offset + source.len(), 0.0);
+    }
+    for (out, sample) in target[offset..].iter_mut().zip(source) {
+        *out += sample * gain;
+    }
+}
+
+/// 短促的"哔"声，音调快速下滑，用于放置方块
+pub fn blip(freq: f32, duration: f32) -> Vec<f32> {
+    tone(Waveform::Square, freq, freq * 0.5, duration, 0.25)
+}
+
+/// 钟声：基频加两个泛音，用于消除
+pub fn chime(freq: f32, duration: f32) -> Vec<f32> {
+    let mut samples = tone(Waveform::Sine, freq, freq, duration, 0.5);
+    mix_into(&mut samples, &tone(Waveform::Sine, freq * 2.0, freq * 2.0, duration * 0.6, 0.5), 0, 0.5);
+    mix_into(&mut samples, &tone(Waveform::Sine, freq * 3.0, freq * 3.0, duration * 0.3, 0.5), 0, 0.25);
+    samples
+}
+
+/// 琶音：按semitones依次演奏相对base的音，每个音持续note_length秒，尾音延长
+pub fn arpeggio(base: f32, semitones: &[i32], note_length: f32, waveform: Waveform) -> Vec<f32> {
+    let step = sample_count(note_length);
+    let mut samples = Vec::new();
+    for (i, &offset) in semitones.iter().enumerate() {
+        let freq = semitone(base, offset);
+        // 每个音比间隔长一倍，和后一个音略有重叠，听起来更连贯
+        let note = tone(waveform, freq, freq, note_length * 2.0, 0.35);
+        mix_into(&mut samples, &note, i * step, 1.0);
+    }
+    samples
+}
+
+/// 可无缝循环的背景音乐：每个和弦一小节（4拍），低音持续弹根音，高音按八分音符往复琶音
+/// chords 为相对root的三和弦半音数，超出结尾的尾音绕回开头，循环播放时没有接缝
+pub fn music_loop(root: f32, chords: &[[i32; 3]], bpm: f32) -> Vec<f32> {
+    let beat = 60.0 / bpm;
+    let bar = sample_count(beat * 4.0);
+    let eighth = sample_count(beat / 2.0);
+    let mut samples = vec![0.0; bar * chords.len()];
+    // 琶音在和弦内音上往复：1 3 5 8 5 3 1 3
+    let pattern = [0, 1, 2, 3, 2, 1, 0, 1];
+
+    for (i, chord) in chords.iter().enumerate() {
+        let start = i * bar;
+
+        let bass_freq = semitone(root, chord[0] - 12);
+        let bass = tone(Waveform::Sine, bass_freq, bass_freq, beat * 4.0, 0.3);
+        mix_wrapped(&mut samples, &bass, start, 1.0);
+
+        for (step, &note) in pattern.iter().enumerate() {
+            let offset = if note == 3 { chord[0] + 12 } else { chord[note] };
+            let freq = semitone(root, offset + 12);
+            let arp = tone(Waveform::Triangle, freq, freq, beat * 0.75, 0.12);
+            mix_wrapped(&mut samples, &arp, start + step * eighth, 1.0);
+        }
+    }
+    samples
+}
+
+// 叠加时超出target结尾的部分绕回开头
+fn mix_wrapped(target: &mut [f32], source: &[f32], offset: usize, gain: f32) {
+    let len = target.len();
+    if len == 0 {
+        return;
+    }
+    for (i, sample) in source.iter().enumerate() {
+        target[(offset + i) % len] += sample * gain;
+    }
+}
+
+/// 把采样限制在 [-1, 1] 内并编码为16位单声道PCM的WAV文件
+pub fn to_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());        // fmt块大小
+    wav.extend_from_slice(&1u16.to_le_bytes());         // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes());         // 单声道
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // 字节率
+    wav.extend_from_slice(&2u16.to_le_bytes());         // 块对齐
+    wav.extend_from_slice(&16u16.to_le_bytes());        // 位深
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tone_length_and_amplitude() {
+        let samples = tone(Waveform::Square, 440.0, 220.0, 0.1, 0.25);
+        assert_eq!(samples.len(), sample_count(0.1));
+        assert_eq!(samples.len(), 4410);
+        assert!(samples.iter().all(|s| s.abs() <= 0.25));
+        // 起音从0开始，衰减到结尾接近0
+        assert_eq!(samples[0], 0.0);
+        assert!(samples.last().unwrap().abs() < 1e-3);
+        assert!(samples.iter().any(|s| s.abs() > 0.2));
+        assert!(tone(Waveform::Sine, 440.0, 440.0, -1.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn wav_header_and_data_size() {
+        let samples = vec![0.0; 100];
+        let wav = to_wav(&samples);
+        assert_eq!(wav.len(), 44 + 200);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(wav[4..8].try_into().unwrap()), 36 + 200);
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), SAMPLE_RATE);
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 200);
+    }
+
+    #[test]
+    fn wav_clamps_samples() {
+        let wav = to_wav(&[2.0, -2.0, 0.5]);
+        let value = |i: usize| i16::from_le_bytes([wav[44 + i * 2], wav[45 + i * 2]]);
+        assert_eq!(value(0), i16::MAX);
+        assert_eq!(value(1), -i16::MAX);
+        assert_eq!(value(2), (0.5 * i16::MAX as f32) as i16);
+    }
+
+    #[test]
+    fn music_loop_length_matches_tempo() {
+        let chords = [[0, 4, 7], [5, 9, 12], [7, 11, 14], [0, 4, 7]];
+        // 120拍每分钟，每小节4拍为2秒
+        let samples = music_loop(220.0, &chords, 120.0);
+        assert_eq!(samples.len(), sample_count(2.0) * chords.len());
+        assert!(samples.iter().all(|s| s.is_finite()));
+        assert!(music_loop(220.0, &[], 120.0).is_empty());
+    }
+
+    #[test]
+    fn mix_wrapped_wraps_around() {
+        let mut target = vec![0.0; 4];
+        mix_wrapped(&mut target, &[1.0, 2.0, 3.0], 2, 0.5);
+        assert_eq!(target, vec![1.5, 0.0, 0.5, 1.0]);
+        // 空目标不做任何事
+        mix_wrapped(&mut [], &[1.0], 3, 1.0);
+    }
+}
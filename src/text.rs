@@ -0,0 +1,509 @@
+// 文本绘制和排版：字体管理、对齐、自动换行、阴影和描边
+// 内嵌的中文字体是只含字符串表用字的子集（由 subset_font 工具生成），启动时加载
+// 需要绘制子集中没有的字符时，再从 resources/fonts 读取完整字体替换子集，读取失败时继续使用子集
+// 其他语言需要的字体（如日文）启动时从 resources/fonts 读取，优先读取 subset_font 生成的子集
+// 字体无法加载时 locale 模块把文本退回英文，用macroquad默认字体显示
+// 垂直对齐使用字体文件 hhea 表中的上升/下降高度，不同文字的基线位置一致
+use macroquad::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+use crate::display;
+use crate::font_subset;
+use crate::locale;
+
+// 将子集字体直接嵌入到可执行文件中，完整字体按需从文件加载
+const EMBEDDED_FONT_NAME: &str = "SourceHanSansCN-Medium";
+const EMBEDDED_FONT_DATA: &[u8] = include_bytes!("../resources/fonts/SourceHanSansCN-Medium.subset.ttf");
+
+// 子集字体包含的字符
+static EMBEDDED_CHARS: Lazy<HashSet<char>> = Lazy::new(|| font_subset::char_set(EMBEDDED_FONT_DATA));
+
+// 完整中文字体的加载状态
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum FullFont {
+    NotNeeded,  // 目前只绘制过子集中有的字符
+    Wanted,     // 需要加载，由主循环中的 load_pending_fonts 加载
+    Loaded,
+    Failed,     // 加载失败，继续使用子集
+}
+
+static FULL_FONT: Lazy<Mutex<FullFont>> = Lazy::new(|| Mutex::new(FullFont::NotNeeded));
+
+// 已加载的字体和它的纵向度量（相对字号的比例）
+struct LoadedFont {
+    font: Font,
+    ascent: f32,   // 基线以上的高度
+    descent: f32,  // 基线以下的深度（正数）
+}
+
+// 已加载的字体，按名称（字体文件名去掉扩展名）索引
+static FONTS: Lazy<Mutex<HashMap<String, LoadedFont>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// 加载失败的字体，不再重试
+static FAILED_FONTS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+// 主题指定的字体，加载成功后代替语言默认的字体
+static THEME_FONT: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+
+// 从TrueType/OpenType字体的 head 和 hhea 表读取上升和下降高度，返回相对字号的比例
+fn read_line_metrics(data: &[u8]) -> Option<(f32, f32)> {
+    let u16_at = |offset: usize| data.get(offset..offset + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    let u32_at = |offset: usize| data.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let table = |tag: &[u8; 4]| {
+        (0..u16_at(4)? as usize)
+            .map(|i| 12 + i * 16)
+            .find(|&record| data.get(record..record + 4) == Some(&tag[..]))
+            .and_then(|record| u32_at(record + 8))
+            .map(|offset| offset as usize)
+    };
+    let units_per_em = u16_at(table(b"head")? + 18)? as f32;
+    let hhea = table(b"hhea")?;
+    let ascender = u16_at(hhea + 4)? as i16 as f32;
+    let descender = u16_at(hhea + 6)? as i16 as f32;
+    if units_per_em <= 0.0 {
+        return None;
+    }
+    Some((ascender / units_per_em, -descender / units_per_em))
+}
+
+/// 内嵌的子集字体数据和它的上升/下降高度（相对字号），供不经过macroquad的软件光栅化使用
+pub fn embedded_font() -> (&'static [u8], f32, f32) {
+    let (ascent, descent) = read_line_metrics(EMBEDDED_FONT_DATA).unwrap_or((0.88, 0.12));
+    (EMBEDDED_FONT_DATA, ascent, descent)
+}
+
+// 加载字体并以name登记（替换同名的字体），返回是否成功
+fn add_font(name: &str, data: &[u8]) -> bool {
+    match load_ttf_font_from_bytes(data) {
+        Ok(font) => {
+            // 读不到度量时使用思源黑体的值
+            let (ascent, descent) = read_line_metrics(data).unwrap_or((0.88, 0.12));
+            FONTS.lock().unwrap().insert(name.to_string(), LoadedFont { font, ascent, descent });
+            println!("成功加载字体: {}", name);
+            true
+        },
+        Err(err) => {
+            println!("无法加载字体 {}: {:?}", name, err);
+            false
+        }
+    }
+}
+
+/// 加载嵌入的中文字体，失败时继续使用默认字体
+pub fn load_embedded_font() {
+    if !add_font(EMBEDDED_FONT_NAME, EMBEDDED_FONT_DATA) {
+        FAILED_FONTS.lock().unwrap().insert(EMBEDDED_FONT_NAME.to_string());
+    }
+}
+
+/// 加载需要的字体：当前语言的字体、主题字体和按需加载的完整中文字体，已加载或已失败时直接返回
+/// 在主循环中每帧调用，这一帧读取了字体文件时返回true
+pub async fn load_pending_fonts() -> bool {
+    let mut loading = false;
+    if *FULL_FONT.lock().unwrap() == FullFont::Wanted {
+        loading = true;
+        let path = format!("resources/fonts/{}.ttf", EMBEDDED_FONT_NAME);
+        // 用同名的完整字体替换子集
+        let loaded = match load_file(&path).await {
+            Ok(data) => add_font(EMBEDDED_FONT_NAME, &data),
+            Err(_) => false,
+        };
+        let state = if loaded {
+            FullFont::Loaded
+        } else {
+            println!("无法加载完整字体 {}，继续使用子集字体", path);
+            FullFont::Failed
+        };
+        *FULL_FONT.lock().unwrap() = state;
+    }
+
+    if let Some(name) = locale::current_locale().font() {
+        loading |= needs_loading(name);
+        if !load_font_file(name).await {
+            println!("使用英文显示");
+        }
+    }
+    let theme_font = THEME_FONT.lock().unwrap().clone();
+    if let Some(name) = theme_font {
+        loading |= needs_loading(&name);
+        if !load_font_file(&name).await {
+            println!("使用语言默认的字体");
+        }
+    }
+    loading
+}
+
+// 字体还没有加载过，也没有加载失败过
+fn needs_loading(name: &str) -> bool {
+    !has_font(name) && !FAILED_FONTS.lock().unwrap().contains(name)
+}
+
+/// 加载所有语言的字体，需要在设置语言之前调用；字体无法加载的语言不出现在设置中
+pub async fn load_locale_fonts() {
+    for locale in locale::Locale::ALL {
+        if let Some(name) = locale.font() {
+            if !load_font_file(name).await {
+                println!("{} 的字体不可用", locale.code());
+            }
+        }
+    }
+}
+
+// 从 resources/fonts 加载还没有加载过的字体，先找子集再找完整字体，返回字体是否可用，失败的字体不再重试
+async fn load_font_file(name: &str) -> bool {
+    if has_font(name) {
+        return true;
+    }
+    if FAILED_FONTS.lock().unwrap().contains(name) {
+        return false;
+    }
+    for path in [format!("resources/fonts/{}.subset.ttf", name), format!("resources/fonts/{}.ttf", name)] {
+        if let Ok(data) = load_file(&path).await {
+            if add_font(name, &data) {
+                return true;
+            }
+        }
+    }
+    println!("找不到字体 {}", name);
+    FAILED_FONTS.lock().unwrap().insert(name.to_string());
+    false
+}
+
+/// 设置主题字体，由 load_pending_fonts 加载，加载前和加载失败时使用语言默认的字体
+pub fn set_theme_font(name: Option<&str>) {
+    *THEME_FONT.lock().unwrap() = name.map(str::to_string);
+}
+
+/// 字体是否已加载
+pub fn has_font(name: &str) -> bool {
+    FONTS.lock().unwrap().contains_key(name)
+}
+
+// 使用子集字体绘制子集中没有的字符时，请求加载完整字体
+fn request_missing_glyphs(text: &str, font_name: Option<&str>) {
+    if font_name != Some(EMBEDDED_FONT_NAME) || EMBEDDED_CHARS.is_empty() {
+        return;
+    }
+    let mut state = FULL_FONT.lock().unwrap();
+    if *state == FullFont::NotNeeded && text.chars().any(|c| !c.is_whitespace() && !EMBEDDED_CHARS.contains(&c)) {
+        *state = FullFont::Wanted;
+    }
+}
+
+/// 水平对齐：x是文本的左边、中心还是右边
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum HAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// 垂直对齐：y是文本块的顶部、中线、第一行的基线还是底部
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum VAlign {
+    Top,
+    Middle,
+    Baseline,
+    Bottom,
+}
+
+/// 文本样式，默认水平居中、垂直居中、不换行
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub font_size: f32,
+    pub color: Color,
+    pub align: HAlign,
+    pub valign: VAlign,
+    pub max_width: Option<f32>,          // 超过这个宽度时自动换行
+    pub line_spacing: f32,               // 行距，相对一行文字高度的倍数
+    pub shadow: Option<(Vec2, Color)>,   // 投影的偏移和颜色
+    pub outline: Option<(f32, Color)>,   // 描边的宽度和颜色
+}
+
+impl TextStyle {
+    pub fn new(font_size: f32, color: Color) -> Self {
+        TextStyle {
+            font_size,
+            color,
+            align: HAlign::Center,
+            valign: VAlign::Middle,
+            max_width: None,
+            line_spacing: 1.2,
+            shadow: None,
+            outline: None,
+        }
+    }
+
+    pub fn align(mut self, align: HAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn valign(mut self, valign: VAlign) -> Self {
+        self.valign = valign;
+        self
+    }
+
+    pub fn wrap(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    pub fn shadow(mut self, offset: Vec2, color: Color) -> Self {
+        self.shadow = Some((offset, color));
+        self
+    }
+
+    pub fn outline(mut self, width: f32, color: Color) -> Self {
+        self.outline = Some((width, color));
+        self
+    }
+}
+
+/// 排版结果：换行后的每一行及其宽度，以及整个文本块的尺寸（像素）
+pub struct TextLayout {
+    pub lines: Vec<(String, f32)>,
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_height: f32,
+    pub size: Vec2,
+}
+
+// 按物理像素光栅化字形：返回 (光栅化字号, 缩放)，两者相乘等于逻辑字号
+fn raster_size(font_size: f32) -> (u16, f32) {
+    let raster = (font_size * display::pixel_ratio()).round().max(1.0);
+    (raster as u16, font_size / raster)
+}
+
+// 在当前显示语言的字体上执行操作，传入字体和按字号换算后的上升/下降高度
+fn with_font<R>(text: &str, font_size: f32, f: impl FnOnce(Option<&Font>, f32, f32) -> R) -> R {
+    let theme_font = THEME_FONT.lock().unwrap().clone().filter(|name| has_font(name));
+    let font_name = theme_font.as_deref().or(locale::display_locale().font());
+    request_missing_glyphs(text, font_name);
+    let fonts = FONTS.lock().unwrap();
+    match font_name.and_then(|name| fonts.get(name)) {
+        Some(loaded) => f(Some(&loaded.font), loaded.ascent * font_size, loaded.descent * font_size),
+        None => {
+            // 默认字体没有字体文件，用字形实际的范围代替
+            let (raster, scale) = raster_size(font_size);
+            let dims = measure_text("Ag", None, raster, scale);
+            f(None, dims.offset_y, dims.height - dims.offset_y)
+        }
+    }
+}
+
+// 中日韩文字和全角符号，字与字之间可以换行
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32, 0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF)
+}
+
+// 不能出现在行首的标点（句号、逗号、右括号、长音符等）
+fn no_break_before(c: char) -> bool {
+    "，。、！？：；）」』》〉…ー・,.!?:;)]}%".contains(c)
+}
+
+// 不能出现在行尾的标点（左括号等）
+fn no_break_after(c: char) -> bool {
+    "（「『《〈([{".contains(c)
+}
+
+// 把一段文本切成不可分割的片段：拉丁文按单词（带上后面的空格），中日文按单个字符，标点跟随相邻的字
+fn break_units(text: &str) -> Vec<&str> {
+    let mut units = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        if let Some(p) = prev {
+            let boundary = (p.is_whitespace() && !c.is_whitespace()) || is_cjk(c) || is_cjk(p);
+            if boundary && !no_break_before(c) && !no_break_after(p) {
+                units.push(&text[start..i]);
+                start = i;
+            }
+        }
+        prev = Some(c);
+    }
+    if start < text.len() {
+        units.push(&text[start..]);
+    }
+    units
+}
+
+// 按最大宽度贪心换行，单个片段比最大宽度还宽时单独占一行
+fn wrap_paragraph(paragraph: &str, max_width: f32, measure: &impl Fn(&str) -> f32, lines: &mut Vec<String>) {
+    let mut line = String::new();
+    for unit in break_units(paragraph) {
+        let candidate = format!("{}{}", line, unit);
+        if !line.trim_end().is_empty() && measure(candidate.trim_end()) > max_width {
+            lines.push(line.trim_end().to_string());
+            line = unit.trim_start().to_string();
+        } else {
+            line = candidate;
+        }
+    }
+    lines.push(line.trim_end().to_string());
+}
+
+/// 用给定的宽度度量按样式换行和排版，ascent和descent为按字号换算后的上升/下降高度
+/// 软件光栅化使用自己的字体度量，与屏幕上的排版规则相同
+pub fn layout_lines(text: &str, style: &TextStyle, ascent: f32, descent: f32, measure: impl Fn(&str) -> f32) -> TextLayout {
+    let mut raw_lines = Vec::new();
+    for paragraph in text.split('\n') {
+        match style.max_width {
+            Some(max_width) => wrap_paragraph(paragraph, max_width, &measure, &mut raw_lines),
+            None => raw_lines.push(paragraph.to_string()),
+        }
+    }
+
+    let lines: Vec<(String, f32)> = raw_lines.into_iter().map(|line| {
+        let width = measure(&line);
+        (line, width)
+    }).collect();
+    let line_height = (ascent + descent) * style.line_spacing;
+    let width = lines.iter().map(|(_, w)| *w).fold(0.0, f32::max);
+    let height = ascent + descent + line_height * (lines.len().max(1) - 1) as f32;
+    TextLayout { lines, ascent, descent, line_height, size: Vec2::new(width, height) }
+}
+
+/// 文本块左上角的位置：对齐方式决定 (x, y) 是文本块的哪个位置
+pub fn block_origin(layout: &TextLayout, x: f32, y: f32, style: &TextStyle) -> Vec2 {
+    let top = match style.valign {
+        VAlign::Top => y,
+        VAlign::Middle => y - layout.size.y / 2.0,
+        VAlign::Baseline => y - layout.ascent,
+        VAlign::Bottom => y - layout.size.y,
+    };
+    Vec2::new(line_left(layout.size.x, x, style), top)
+}
+
+/// 宽度为width的一行文字的左边位置
+pub fn line_left(width: f32, x: f32, style: &TextStyle) -> f32 {
+    match style.align {
+        HAlign::Left => x,
+        HAlign::Center => x - width / 2.0,
+        HAlign::Right => x - width,
+    }
+}
+
+fn layout_with(text: &str, style: &TextStyle, font: Option<&Font>, ascent: f32, descent: f32) -> TextLayout {
+    let (raster, scale) = raster_size(style.font_size);
+    layout_lines(text, style, ascent, descent, |s: &str| measure_text(s, font, raster, scale).width)
+}
+
+/// 按样式排版文本（换行和度量），不绘制
+pub fn layout_text(text: &str, style: &TextStyle) -> TextLayout {
+    with_font(text, style.font_size, |font, ascent, descent| layout_with(text, style, font, ascent, descent))
+}
+
+/// 文本按样式在 (x, y) 绘制时占据的矩形，与 draw_text_styled 的返回值相同
+pub fn text_rect(text: &str, x: f32, y: f32, style: &TextStyle) -> Rect {
+    if style.font_size < 1.0 {
+        return Rect::new(x, y, 0.0, 0.0);
+    }
+    let layout = layout_text(text, style);
+    let top = block_origin(&layout, x, y, style).y;
+    Rect::new(line_left(layout.size.x, x, style), top, layout.size.x, layout.size.y)
+}
+
+/// 按样式在 (x, y) 绘制文本，对齐方式决定 (x, y) 是文本块的哪个位置，返回文本块占据的矩形
+pub fn draw_text_styled(text: &str, x: f32, y: f32, style: &TextStyle) -> Rect {
+    if style.font_size < 1.0 {
+        return Rect::new(x, y, 0.0, 0.0);
+    }
+    with_font(text, style.font_size, |font, ascent, descent| {
+        let layout = layout_with(text, style, font, ascent, descent);
+        let top = block_origin(&layout, x, y, style).y;
+        let left_of = |width: f32| line_left(width, x, style);
+
+        let (raster, scale) = raster_size(style.font_size);
+        let draw_pass = |offset: Vec2, color: Color| {
+            for (i, (line, width)) in layout.lines.iter().enumerate() {
+                let baseline = top + ascent + i as f32 * layout.line_height;
+                draw_text_ex(line, left_of(*width) + offset.x, baseline + offset.y, TextParams {
+                    font_size: raster,
+                    font_scale: scale,
+                    font,
+                    color,
+                    ..Default::default()
+                });
+            }
+        };
+        if let Some((offset, color)) = style.shadow {
+            draw_pass(offset, color);
+        }
+        if let Some((width, color)) = style.outline {
+            // 向八个方向偏移绘制描边色，再在上面绘制正文
+            for i in 0..8 {
+                let angle = i as f32 * std::f32::consts::FRAC_PI_4;
+                draw_pass(Vec2::new(angle.cos(), angle.sin()) * width, color);
+            }
+        }
+        draw_pass(Vec2::ZERO, style.color);
+
+        Rect::new(left_of(layout.size.x), top, layout.size.x, layout.size.y)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个字符固定10像素宽
+    fn fixed(s: &str) -> f32 {
+        s.chars().count() as f32 * 10.0
+    }
+
+    fn wrap(text: &str, max_width: f32) -> Vec<String> {
+        let style = TextStyle::new(10.0, WHITE).wrap(max_width);
+        layout_lines(text, &style, 8.0, 2.0, fixed).lines.into_iter().map(|(line, _)| line).collect()
+    }
+
+    #[test]
+    fn cjk_breaks_between_characters() {
+        assert_eq!(break_units("方块消除"), vec!["方", "块", "消", "除"]);
+        assert_eq!(wrap("方块消除游戏", 40.0), vec!["方块消除", "游戏"]);
+    }
+
+    #[test]
+    fn latin_words_stay_whole() {
+        assert_eq!(break_units("block blast game"), vec!["block ", "blast ", "game"]);
+        assert_eq!(wrap("block blast game", 120.0), vec!["block blast", "game"]);
+    }
+
+    #[test]
+    fn closing_punctuation_never_starts_a_line() {
+        assert_eq!(break_units("好。再来"), vec!["好。", "再", "来"]);
+        // 句号放不下时和前一个字一起换到下一行
+        let lines = wrap("方块消除。", 40.0);
+        assert_eq!(lines, vec!["方块消", "除。"]);
+        assert!(lines.iter().all(|line| !line.starts_with(no_break_before)));
+        // 左括号跟着后面的字
+        assert_eq!(break_units("（好）"), vec!["（好）"]);
+    }
+
+    #[test]
+    fn over_long_word_takes_its_own_line() {
+        assert_eq!(wrap("a supercalifragilistic word", 60.0), vec!["a", "supercalifragilistic", "word"]);
+        assert_eq!(wrap("supercalifragilistic", 10.0), vec!["supercalifragilistic"]);
+        assert_eq!(wrap("", 10.0), vec![""]);
+    }
+
+    #[test]
+    fn layout_measures_lines_and_aligns() {
+        let style = TextStyle::new(10.0, WHITE).wrap(40.0);
+        let layout = layout_lines("方块消除游戏\n好", &style, 8.0, 2.0, fixed);
+        let widths: Vec<f32> = layout.lines.iter().map(|(_, w)| *w).collect();
+        assert_eq!(widths, vec![40.0, 20.0, 10.0]);
+        assert_eq!(layout.line_height, 12.0);
+        assert_eq!(layout.size, Vec2::new(40.0, 10.0 + 12.0 * 2.0));
+
+        assert_eq!(line_left(20.0, 100.0, &style.align(HAlign::Left)), 100.0);
+        assert_eq!(line_left(20.0, 100.0, &style.align(HAlign::Center)), 90.0);
+        assert_eq!(line_left(20.0, 100.0, &style.align(HAlign::Right)), 80.0);
+
+        let origin = |valign| block_origin(&layout, 100.0, 50.0, &style.valign(valign));
+        assert_eq!(origin(VAlign::Top), Vec2::new(80.0, 50.0));
+        assert_eq!(origin(VAlign::Middle), Vec2::new(80.0, 33.0));
+        assert_eq!(origin(VAlign::Baseline), Vec2::new(80.0, 42.0));
+        assert_eq!(origin(VAlign::Bottom), Vec2::new(80.0, 16.0));
+    }
+}
@@ -0,0 +1,350 @@
+// 主题：方块调色板、界面颜色、方块立体效果、字体和粒子颜色
+// 主题文件位于 resources/themes/<名称>.txt，格式见 classic.txt 的文件头
+// 内置主题编译时嵌入；另外可以放一个 resources/themes/custom.txt 作为自定义主题，启动时加载
+// 网格和方块只保存调色板索引，切换主题后已放下的方块也立即换色
+// 色盲模式下使用主题的色盲安全调色板，并在方块上叠加每种颜色特有的图案，不依赖颜色也能区分方块
+use std::sync::{Arc, Mutex};
+use once_cell::sync::Lazy;
+
+use macroquad::prelude::*;
+
+use crate::block::PALETTE_SIZE;
+
+/// 方块的立体效果：左上边缘提亮、右下边缘压暗
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bevel {
+    pub light: f32,   // 高光颜色的增量
+    pub dark: f32,    // 阴影颜色的减量
+    pub border: f32,  // 边缘厚度（相对格子大小），为0时不绘制边缘
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub palette: [Color; PALETTE_SIZE],  // 方块颜色，按调色板索引
+    pub colorblind_palette: [Color; PALETTE_SIZE],  // 色盲模式下的方块颜色
+    pub glyphs: bool,           // 在方块上绘制图案（色盲模式开启时为true，不能在主题文件中设置）
+    pub background: Color,
+    pub grid_background: Color,
+    pub grid_border: Color,
+    pub grid_lines: Color,
+    pub tray_background: Color,
+    pub separator: Color,
+    pub text: Color,            // 对局界面的文字
+    pub accent: Color,          // 提示文字
+    pub bevel: Bevel,
+    pub font: Option<String>,   // 界面字体，None时使用语言默认的字体
+    pub spark: Color,           // 消除时叠加混合的亮点
+    pub star_start: Color,      // 连击星星的起始颜色
+    pub star_end: Color,        // 连击星星消失时的颜色
+}
+
+/// 内置主题的名称和文件内容，第一个为默认主题
+const BUILTIN: [(&str, &str); 4] = [
+    ("classic", include_str!("../resources/themes/classic.txt")),
+    ("dark", include_str!("../resources/themes/dark.txt")),
+    ("pastel", include_str!("../resources/themes/pastel.txt")),
+    ("high-contrast", include_str!("../resources/themes/high-contrast.txt")),
+];
+
+/// 默认主题的名称
+pub const DEFAULT_THEME: &str = BUILTIN[0].0;
+
+/// 自定义主题文件
+pub const CUSTOM_THEME_PATH: &str = "resources/themes/custom.txt";
+
+// 所有可选的主题，内置主题在前，自定义主题加载后追加在后面
+static THEMES: Lazy<Mutex<Vec<Arc<Theme>>>> = Lazy::new(|| {
+    let mut themes: Vec<Arc<Theme>> = Vec::new();
+    for (_, source) in BUILTIN {
+        let base = themes.first().map(|theme| (**theme).clone()).unwrap_or_else(Theme::fallback);
+        let theme = Theme::parse(source, &base, &|name| find_builtin(&themes, name));
+        themes.push(Arc::new(theme));
+    }
+    Mutex::new(themes)
+});
+
+// 玩家选择的主题和是否开启色盲模式
+struct Selection {
+    theme: Arc<Theme>,
+    colorblind: bool,
+}
+
+static SELECTION: Lazy<Mutex<Selection>> = Lazy::new(|| {
+    Mutex::new(Selection { theme: THEMES.lock().unwrap()[0].clone(), colorblind: false })
+});
+
+// 实际使用的主题，选择变化时由 apply_selection 重新生成
+static CURRENT: Lazy<Mutex<Arc<Theme>>> = Lazy::new(|| Mutex::new(THEMES.lock().unwrap()[0].clone()));
+
+fn apply_selection(selection: &Selection) {
+    let theme = if selection.colorblind {
+        Arc::new(Theme {
+            palette: selection.theme.colorblind_palette,
+            glyphs: true,
+            ..(*selection.theme).clone()
+        })
+    } else {
+        selection.theme.clone()
+    };
+    *CURRENT.lock().unwrap() = theme;
+}
+
+fn find_builtin(themes: &[Arc<Theme>], name: &str) -> Option<Theme> {
+    themes.iter().find(|theme| theme.name == name).map(|theme| (**theme).clone())
+}
+
+// "#rrggbb" 或 "#rrggbbaa"
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba(channel(0)?, channel(2)?, channel(4)?, alpha))
+}
+
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    value.split(',').map(|item| parse(item.trim())).collect()
+}
+
+impl Theme {
+    // 主题文件都无法解析时使用的颜色
+    fn fallback() -> Self {
+        Theme {
+            name: DEFAULT_THEME.to_string(),
+            palette: [RED, GREEN, BLUE, YELLOW, PURPLE, PINK, ORANGE],
+            colorblind_palette: [RED, GREEN, BLUE, YELLOW, PURPLE, PINK, ORANGE],
+            glyphs: false,
+            background: DARKGRAY,
+            grid_background: BLACK,
+            grid_border: GRAY,
+            grid_lines: BLACK,
+            tray_background: DARKGRAY,
+            separator: GRAY,
+            text: WHITE,
+            accent: GOLD,
+            bevel: Bevel { light: 0.4, dark: 0.4, border: 0.15 },
+            font: None,
+            spark: WHITE,
+            star_start: GOLD,
+            star_end: Color::new(1.0, 0.4, 0.0, 0.0),
+        }
+    }
+
+    /// 解析主题文件，没有写出的键使用base（或文件中 base 指定的主题）的值
+    /// 无效的行打印警告后忽略
+    fn parse(source: &str, base: &Theme, builtin: &dyn Fn(&str) -> Option<Theme>) -> Theme {
+        let (theme, warnings) = Self::parse_checked(source, base, builtin);
+        for warning in warnings {
+            println!("{}", warning);
+        }
+        theme
+    }
+
+    // 解析主题文件，同时返回每个无效行的警告
+    fn parse_checked(source: &str, base: &Theme, builtin: &dyn Fn(&str) -> Option<Theme>) -> (Theme, Vec<String>) {
+        let mut theme = base.clone();
+        let mut warnings = Vec::new();
+        let lines = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            let Some((key, value)) = line.split_once('=') else {
+                warnings.push(format!("主题文件中无法识别的行: {}", line));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if !theme.set_field(key, value, builtin) {
+                warnings.push(format!("主题文件中无效的设置: {} = {}", key, value));
+            }
+        }
+        (theme, warnings)
+    }
+
+    // 设置一个字段，不认识的键或无效的值返回false，保持原值
+    fn set_field(&mut self, key: &str, value: &str, builtin: &dyn Fn(&str) -> Option<Theme>) -> bool {
+        let color = |field: &mut Color| parse_color(value).map(|c| *field = c).is_some();
+        match key {
+            "base" => match builtin(value) {
+                Some(base) => {
+                    *self = Theme { name: self.name.clone(), ..base };
+                    true
+                },
+                None => false,
+            },
+            "name" if !value.is_empty() => {
+                self.name = value.to_string();
+                true
+            },
+            "palette" | "colorblind_palette" => {
+                let Some(palette) = parse_list(value, parse_color).and_then(|colors| colors.try_into().ok()) else {
+                    return false;
+                };
+                if key == "palette" {
+                    self.palette = palette;
+                } else {
+                    self.colorblind_palette = palette;
+                }
+                true
+            },
+            "background" => color(&mut self.background),
+            "grid_background" => color(&mut self.grid_background),
+            "grid_border" => color(&mut self.grid_border),
+            "grid_lines" => color(&mut self.grid_lines),
+            "tray_background" => color(&mut self.tray_background),
+            "separator" => color(&mut self.separator),
+            "text" => color(&mut self.text),
+            "accent" => color(&mut self.accent),
+            "spark" => color(&mut self.spark),
+            "star_start" => color(&mut self.star_start),
+            "star_end" => color(&mut self.star_end),
+            "bevel" => match parse_list(value, |v| v.parse::<f32>().ok()).as_deref() {
+                Some(&[light, dark, border]) if (0.0..=0.5).contains(&border) => {
+                    self.bevel = Bevel { light, dark, border };
+                    true
+                },
+                _ => false,
+            },
+            "font" => {
+                self.font = Some(value.to_string()).filter(|name| !name.is_empty());
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// 调色板索引对应的颜色，索引越界时循环使用
+    pub fn block_color(&self, index: u8) -> Color {
+        self.palette[index as usize % PALETTE_SIZE]
+    }
+
+    /// 是否为内置主题（显示名称需要翻译）
+    pub fn is_builtin(&self) -> bool {
+        BUILTIN.iter().any(|(name, _)| *name == self.name)
+    }
+}
+
+/// 当前主题（色盲模式下已替换调色板）
+pub fn current() -> Arc<Theme> {
+    CURRENT.lock().unwrap().clone()
+}
+
+/// 所有可选主题的名称，按设置界面中切换的顺序
+pub fn names() -> Vec<String> {
+    THEMES.lock().unwrap().iter().map(|theme| theme.name.clone()).collect()
+}
+
+/// 切换到指定名称的主题，找不到时返回false并保持当前主题
+pub fn set_theme(name: &str) -> bool {
+    let theme = THEMES.lock().unwrap().iter().find(|theme| theme.name == name).cloned();
+    let Some(theme) = theme else { return false };
+    let mut selection = SELECTION.lock().unwrap();
+    selection.theme = theme;
+    apply_selection(&selection);
+    true
+}
+
+/// 开关色盲模式：使用色盲安全的调色板并在方块上绘制图案
+pub fn set_colorblind(colorblind: bool) {
+    let mut selection = SELECTION.lock().unwrap();
+    selection.colorblind = colorblind;
+    apply_selection(&selection);
+}
+
+/// 解析主题文件并加入可选主题，与已有的自定义主题同名时替换，返回主题名称
+/// 没有名称或与内置主题同名时命名为 custom
+pub fn register(source: &str) -> String {
+    let mut themes = THEMES.lock().unwrap();
+    let base = Theme { name: String::new(), ..(*themes[0]).clone() };
+    let mut theme = Theme::parse(source, &base, &|name| find_builtin(&themes[..BUILTIN.len()], name));
+    if theme.name.is_empty() || theme.is_builtin() {
+        theme.name = "custom".to_string();
+    }
+    let name = theme.name.clone();
+    match themes.iter().position(|t| t.name == name) {
+        Some(i) => themes[i] = Arc::new(theme),
+        None => themes.push(Arc::new(theme)),
+    }
+    name
+}
+
+/// 加载自定义主题文件，文件不存在时什么也不做
+pub async fn load_custom_theme() {
+    match load_string(CUSTOM_THEME_PATH).await {
+        Ok(source) => {
+            let name = register(&source);
+            println!("已加载自定义主题: {}", name);
+        },
+        Err(_) => println!("没有自定义主题文件 {}", CUSTOM_THEME_PATH),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 默认主题必须写出的键，其它内置主题可以用 base 继承
+    const REQUIRED_KEYS: [&str; 15] = [
+        "name", "palette", "colorblind_palette", "background", "grid_background", "grid_border", "grid_lines",
+        "tray_background", "separator", "text", "accent", "bevel", "spark", "star_start", "star_end",
+    ];
+
+    fn keys(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn bundled_themes_parse_cleanly() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/resources/themes");
+        let mut files: Vec<_> = std::fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt") && !path.ends_with(CUSTOM_THEME_PATH))
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), BUILTIN.len());
+
+        let mut parsed: Vec<Arc<Theme>> = Vec::new();
+        for (name, source) in BUILTIN {
+            let file = std::fs::read_to_string(format!("{}/{}.txt", dir, name)).unwrap();
+            assert_eq!(file, source, "{} 与嵌入的内容不同", name);
+            let base = parsed.first().map(|theme| (**theme).clone()).unwrap_or_else(Theme::fallback);
+            let (theme, warnings) = Theme::parse_checked(source, &base, &|name| find_builtin(&parsed, name));
+            assert!(warnings.is_empty(), "{}: {:?}", name, warnings);
+            assert_eq!(theme.name, name);
+            let keys = keys(source);
+            if !keys.contains(&"base") {
+                for key in REQUIRED_KEYS {
+                    assert!(keys.contains(&key), "{} 缺少 {}", name, key);
+                }
+            }
+            parsed.push(Arc::new(theme));
+        }
+        assert_ne!(*parsed[0], Theme::fallback());
+    }
+
+    #[test]
+    fn bad_lines_are_rejected() {
+        let base = Theme::fallback();
+        let source = "# 注释\nno equals sign\nbackground = #12345\npalette = #ff0000, #00ff00\nbevel = 0.4, 0.4, 0.9\nbase = missing\nunknown = #ffffff\ntext = #102030\n";
+        let (theme, warnings) = Theme::parse_checked(source, &base, &|_| None);
+        assert_eq!(warnings.len(), 6, "{:?}", warnings);
+        assert_eq!(theme, Theme { text: Color::from_rgba(0x10, 0x20, 0x30, 255), ..base });
+    }
+
+    #[test]
+    fn colors_parse_with_optional_alpha() {
+        assert_eq!(parse_color("#ff000080"), Some(Color::from_rgba(255, 0, 0, 128)));
+        assert_eq!(parse_color("#00ff00"), Some(Color::from_rgba(0, 255, 0, 255)));
+        assert_eq!(parse_color("00ff00"), None);
+        assert_eq!(parse_color("#gg0000"), None);
+        assert_eq!(parse_color("#é0000"), None);
+    }
+}
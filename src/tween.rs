@@ -0,0 +1,107 @@
+// 补间动画：缓动函数 + 时长 + 插值
+// 用于候选方块的拖拽放大、滑回候选区和吸附到网格
+use macroquad::prelude::*;
+
+/// 缓动函数，输入为 [0, 1] 内的进度，输出从0到1（BackOut 中途会略大于1）
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicOut,
+    BackOut,  // 略微越过终点再回弹
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::BackOut => {
+                let overshoot = 1.70158;
+                let u = t - 1.0;
+                1.0 + (overshoot + 1.0) * u.powi(3) + overshoot * u.powi(2)
+            }
+        }
+    }
+}
+
+/// 可以线性插值的值
+pub trait Lerp: Copy {
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, to: Self, t: f32) -> Self {
+        Color::new(
+            Lerp::lerp(self.r, to.r, t),
+            Lerp::lerp(self.g, to.g, t),
+            Lerp::lerp(self.b, to.b, t),
+            Lerp::lerp(self.a, to.a, t),
+        )
+    }
+}
+
+/// 在duration秒内按缓动函数从from变化到to
+#[derive(Clone, Copy, Debug)]
+pub struct Tween<T: Lerp> {
+    pub from: T,
+    pub to: T,
+    pub duration: f32,
+    pub easing: Easing,
+    elapsed: f32,
+}
+
+impl<T: Lerp> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Self {
+        Tween { from, to, duration, easing, elapsed: 0.0 }
+    }
+
+    /// 已经结束、停在value的补间
+    pub fn fixed(value: T) -> Self {
+        Tween::new(value, value, 0.0, Easing::Linear)
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    /// 缓动后的进度，BackOut等缓动可能略超出 [0, 1]
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        self.easing.apply(self.elapsed / self.duration)
+    }
+
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.progress())
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}